@@ -0,0 +1,157 @@
+use std::{fmt, io::Write};
+
+use crate::{error::Error, located::Located, opt::Opt, parser::Parser, result::Result};
+
+/// An iterator over the options of a [`Parser`](struct.Parser.html), silently skipping errors.
+///
+/// This `struct` is created by [`Parser::ok_only`](struct.Parser.html#method.ok_only).
+#[derive(Debug)]
+pub struct OkOnly<'a> {
+    pub(crate) parser: &'a mut Parser,
+}
+
+impl<'a> Iterator for OkOnly<'a> {
+    type Item = Opt;
+
+    fn next(&mut self) -> Option<Opt> {
+        loop {
+            match self.parser.next() {
+                None => return None,
+                Some(Err(_)) => (),
+                Some(Ok(opt)) => return Some(opt),
+            }
+        }
+    }
+}
+
+/// An iterator over the options of a [`Parser`](struct.Parser.html), passing errors to a
+/// callback.
+///
+/// This `struct` is created by [`Parser::lenient`](struct.Parser.html#method.lenient).
+pub struct Lenient<'a, F> {
+    pub(crate) parser: &'a mut Parser,
+    pub(crate) on_error: F,
+}
+
+impl<'a, F> fmt::Debug for Lenient<'a, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lenient")
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+
+impl<'a, F: FnMut(Error)> Iterator for Lenient<'a, F> {
+    type Item = Opt;
+
+    fn next(&mut self) -> Option<Opt> {
+        loop {
+            match self.parser.next() {
+                None => return None,
+                Some(Err(error)) => (self.on_error)(error),
+                Some(Ok(opt)) => return Some(opt),
+            }
+        }
+    }
+}
+
+/// An iterator over the options of a [`Parser`](struct.Parser.html) that ends after the first
+/// error.
+///
+/// This `struct` is created by [`Parser::strict`](struct.Parser.html#method.strict).
+#[derive(Debug)]
+pub struct Strict<'a> {
+    pub(crate) parser: &'a mut Parser,
+    pub(crate) done: bool,
+}
+
+impl<'a> Iterator for Strict<'a> {
+    type Item = Result<Opt>;
+
+    fn next(&mut self) -> Option<Result<Opt>> {
+        if self.done {
+            return None;
+        }
+
+        let next = self.parser.next();
+        match next {
+            None | Some(Err(_)) => self.done = true,
+            Some(Ok(_)) => (),
+        }
+        next
+    }
+}
+
+/// An iterator over the options of a [`Parser`](struct.Parser.html) that attaches a program name
+/// to every error.
+///
+/// This `struct` is created by [`Parser::with_context`](struct.Parser.html#method.with_context).
+#[derive(Debug)]
+pub struct WithContext<'a> {
+    pub(crate) parser: &'a mut Parser,
+    pub(crate) name: String,
+}
+
+impl<'a> Iterator for WithContext<'a> {
+    type Item = Result<Opt>;
+
+    fn next(&mut self) -> Option<Result<Opt>> {
+        match self.parser.next() {
+            Some(Err(mut error)) => {
+                error.set_program_name(&self.name);
+                Some(Err(error))
+            },
+            next => next,
+        }
+    }
+}
+
+/// An iterator over the options of a [`Parser`](struct.Parser.html) that prints every error to a
+/// sink, as well as returning it.
+///
+/// This `struct` is created by
+/// [`Parser::report_errors`](struct.Parser.html#method.report_errors).
+#[derive(Debug)]
+pub struct Report<'a, W> {
+    pub(crate) parser: &'a mut Parser,
+    pub(crate) name: String,
+    pub(crate) sink: W,
+}
+
+impl<'a, W: Write> Iterator for Report<'a, W> {
+    type Item = Result<Opt>;
+
+    fn next(&mut self) -> Option<Result<Opt>> {
+        match self.parser.next() {
+            Some(Err(mut error)) => {
+                error.set_program_name(&self.name);
+                // failing to report an error must not hide it from the caller
+                let _ = writeln!(self.sink, "{}", error);
+                Some(Err(error))
+            },
+            next => next,
+        }
+    }
+}
+
+/// An iterator over the options of a [`Parser`](struct.Parser.html), each with where it was found
+/// in the arguments.
+///
+/// This `struct` is created by
+/// [`Parser::with_positions`](struct.Parser.html#method.with_positions).
+#[derive(Debug)]
+pub struct WithPositions<'a> {
+    pub(crate) parser: &'a mut Parser,
+}
+
+impl<'a> Iterator for WithPositions<'a> {
+    type Item = Result<Located>;
+
+    fn next(&mut self) -> Option<Result<Located>> {
+        match self.parser.next() {
+            None => None,
+            Some(Err(error)) => Some(Err(error)),
+            Some(Ok(opt)) => Some(Ok(self.parser.locate(opt))),
+        }
+    }
+}
@@ -1,233 +1,126 @@
 extern crate getopt;
 
-use std::{io, process};
+use std::{env, path::Path, process};
 
-use getopt::Opt;
+use getopt::cli;
 
-// Command-line program boilerplate
-mod program {
-    use std::{env, io, path::Path};
-
-    pub use self::Result::*;
-
-    pub enum Result {
-        Ok(i32),
-        External(io::Error),
-        Internal(io::Error),
-    }
-
-    pub fn args() -> Vec<String> {
-        env::args_os()
-            .map(|a| a.to_string_lossy().into_owned())
-            .collect()
-    }
+fn main() -> ! {
+    let args = cli::args();
+    let mut settings = cli::Settings::default();
+    settings.color = use_color();
+    settings.optstring = env::var("GETOPT_OPTSTRING").ok();
+
+    // undocumented: check our output against the system getopt(1)
+    let output = if args.get(1).map(String::as_str) == Some("--compare") {
+        compare(&args, &cli::name("getopt"), &settings)
+    } else {
+        cli::run(&args, &settings)
+    };
 
-    pub fn name(default: &str) -> String {
-        match env::args_os().next() {
-            None => String::from(default),
-            Some(os_string) => match Path::new(&os_string).file_stem() {
-                None => String::from(default),
-                Some(os_str) => os_str.to_string_lossy().into_owned(),
-            },
-        }
-    }
+    print!("{}", output.stdout);
+    eprint!("{}", output.stderr);
+    process::exit(output.status);
 }
 
-enum ShellKind {
-    Bourne,
-    C,
-    Fish,
-    Rc,
+// Colour is only worth it on a terminal, and https://no-color.org asks us to stay plain when
+// NO_COLOR is set to anything but an empty string.
+fn use_color() -> bool {
+    match env::var_os("NO_COLOR") {
+        Some(ref value) if !value.is_empty() => false,
+        _ => stderr_is_terminal(),
+    }
 }
 
-fn main() -> ! {
-    process::exit(match program(&program::name("getopt")) {
-        program::Ok(code) => code,
-        program::External(error) => {
-            eprintln!("{}", error);
-            1
-        },
-        program::Internal(error) => {
-            eprintln!("{}", error);
-            2
-        },
-    });
+#[cfg(unix)]
+fn stderr_is_terminal() -> bool {
+    extern "C" {
+        fn isatty(fd: std::os::raw::c_int) -> std::os::raw::c_int;
+    }
+    unsafe { isatty(2) == 1 }
 }
 
-#[rustfmt::skip]
-fn print_usage(program_name: &str) {
-    println!("Usage: {} [-h] [-n name] [-s shell] optstring [args ...]", program_name);
-    println!("  -n name   report errors as 'name' (default '{}')", program_name);
-    println!("  -s shell  use quoting conventions for shell (default 'sh')");
-    println!();
-    println!("  -h        display this help");
+#[cfg(not(unix))]
+fn stderr_is_terminal() -> bool {
+    false
 }
 
-fn program(name: &str) -> program::Result {
-    let args = program::args();
-    let mut parsed: Vec<String> = Vec::new();
-
-    let mut child_name = name.to_string();
-    let mut shell = ShellKind::Bourne;
-
-    // gather our own options
-    let mut opts = getopt::Parser::new(&args, "hn:s:");
-    loop {
-        match opts.next() {
-            None => break,
-            Some(Err(error)) => {
-                return program::Internal(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("{}: {}", name, error),
-                ));
-            },
-            Some(Ok(opt)) => match opt {
-                Opt('n', Some(arg)) => child_name = arg,
-                Opt('s', Some(arg)) => {
-                    shell = match arg.to_lowercase().trim() {
-                        "ash" | "bash" | "dash" | "ksh" | "mksh" | "sh" | "zsh" => {
-                            ShellKind::Bourne
-                        },
-                        "csh" | "tcsh" => ShellKind::C,
-                        "fish" => ShellKind::Fish,
-                        "plan9" | "rc" => ShellKind::Rc,
-                        x => {
-                            return program::Internal(io::Error::new(
-                                io::ErrorKind::InvalidInput,
-                                format!("{}: unknown shell type: {}", name, x),
-                            ));
-                        },
-                    }
-                },
-                Opt('h', None) => {
-                    print_usage(name);
-                    return program::Ok(0);
-                },
-                _ => unreachable!(),
-            },
-        }
+fn compare(args: &[String], name: &str, settings: &cli::Settings) -> cli::Output {
+    let mut stdout = String::new();
+
+    let system = Path::new("/usr/bin/getopt");
+    if !system.exists() {
+        return cli::Output {
+            status: 2,
+            stdout,
+            stderr: format!(
+                "{}: no getopt(1) to compare with at {}\n",
+                name,
+                system.display()
+            ),
+        };
     }
-
-    let optstring = match args.get(opts.index()) {
+    let optstring = match args.get(2) {
         None => {
-            return program::Internal(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("{}: missing optstring argument", name),
-            ));
+            return cli::Output {
+                status: 2,
+                stdout,
+                stderr: format!("{}: missing optstring argument\n", name),
+            };
         },
         Some(s) => s,
     };
-    let index = opts.index() + 1;
 
-    // parse the other options
-    let mut opts = getopt::Parser::new(&args, optstring);
-    opts.set_index(index);
-    loop {
-        match opts.next() {
-            None => break,
-            Some(Err(error)) => {
-                return program::External(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("{}: {}", child_name, error),
-                ));
-            },
-            Some(Ok(Opt(opt, arg))) => {
-                parsed.push(format!("-{}", opt));
-                match arg {
-                    None => (),
-                    Some(s) => parsed.push(quote_for_shell(&s, &shell)),
-                }
-            },
-        }
-    }
-
-    parsed.push("--".to_string());
-
-    for arg in args.clone().split_off(opts.index()) {
-        parsed.push(quote_for_shell(&arg, &shell));
-    }
-
-    println!("{}", parsed.join(" "));
-
-    program::Ok(0)
-}
-
-fn quote_for_shell(string: &str, kind: &ShellKind) -> String {
-    match kind {
-        // most shells (sh, ksh, zsh, bash, (d)ash, etc.) are in this category
-        ShellKind::Bourne => {
-            let e = '\\'; // escape char
-            let q = '\''; // quote char
-            let mut new_string = String::new();
-            new_string.push(q);
-            for c in string.chars() {
-                match c {
-                    '\'' => {
-                        new_string.push(q);
-                        new_string.push(e);
-                        new_string.push(c);
-                        new_string.push(q);
-                    },
-                    _ => new_string.push(c),
-                }
-            }
-            new_string.push(q);
-            new_string
-        },
-
-        ShellKind::C => {
-            let e = '\\'; // escape char
-            let q = '\''; // quote char
-            let mut new_string = String::new();
-            new_string.push(q);
-            for c in string.chars() {
-                match c {
-                    ' ' | '\'' => {
-                        new_string.push(q);
-                        new_string.push(e);
-                        new_string.push(c);
-                        new_string.push(q);
-                    },
-                    _ => new_string.push(c),
-                }
-            }
-            new_string.push(q);
-            new_string
+    // drop "--compare" and run the rest as usual; the system getopt is always given the
+    // optstring argument, so ours must not pick up a default from the environment instead
+    let mut our_args = vec![args[0].clone()];
+    our_args.extend_from_slice(&args[2..]);
+    let mut settings = settings.clone();
+    settings.optstring = None;
+    let ours = cli::run(&our_args, &settings);
+
+    // the util-linux getopt permutes operands unless asked to behave
+    let theirs = match process::Command::new(system)
+        .arg("-o")
+        .arg(optstring)
+        .arg("--")
+        .args(&args[3..])
+        .env("POSIXLY_CORRECT", "1")
+        .output()
+    {
+        Ok(output) => output,
+        Err(error) => {
+            return cli::Output {
+                status: 1,
+                stdout,
+                stderr: format!("{}\n", error),
+            };
         },
+    };
 
-        ShellKind::Fish => {
-            let e = '\\'; // escape char
-            let q = '\''; // quote char
-            let mut new_string = String::new();
-            new_string.push(q);
-            for c in string.chars() {
-                match c {
-                    '\'' | '\\' => {
-                        new_string.push(e);
-                        new_string.push(c);
-                    },
-                    _ => new_string.push(c),
-                }
-            }
-            new_string.push(q);
-            new_string
-        },
+    // error messages are worded differently everywhere, and some implementations still print
+    // whatever they parsed before the error, so on failure only the exit status is compared
+    let mut same = true;
+    if Some(ours.status) != theirs.status.code() {
+        same = false;
+        stdout.push_str(&format!(
+            "exit status: {:?} (ours) vs {:?} ({})\n",
+            Some(ours.status),
+            theirs.status.code(),
+            system.display()
+        ));
+    }
+    let succeeded = ours.status == 0 && theirs.status.success();
+    let theirs = String::from_utf8_lossy(&theirs.stdout);
+    if succeeded && ours.stdout.trim() != theirs.trim() {
+        same = false;
+        stdout.push_str("output differs:\n");
+        stdout.push_str(&format!("  ours:   {}\n", ours.stdout.trim()));
+        stdout.push_str(&format!("  theirs: {}\n", theirs.trim()));
+    }
 
-        ShellKind::Rc => {
-            let q = '\''; // quote char
-            let mut new_string = String::new();
-            new_string.push(q);
-            for c in string.chars() {
-                match c {
-                    '\'' => {
-                        new_string.push(q);
-                        new_string.push(c);
-                    },
-                    _ => new_string.push(c),
-                }
-            }
-            new_string.push(q);
-            new_string
-        },
+    cli::Output {
+        status: if same { 0 } else { 1 },
+        stdout,
+        stderr: String::new(),
     }
 }
@@ -0,0 +1,19 @@
+/// How [`Parser`](struct.Parser.html) would interpret an argument, as returned by
+/// [`Parser::classify`](struct.Parser.html#method.classify).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Class {
+    /// One or more valid options, complete with any argument.
+    Options,
+    /// One or more valid options, the last of which takes its argument from the next element.
+    NeedsArgument(char),
+    /// One or more options, including the given unknown option.
+    Unknown(char),
+    /// The given option, which takes no argument, followed by more characters when grouping is
+    /// off.
+    Unexpected(char),
+    /// The `--` argument marking the end of options.
+    Separator,
+    /// An argument which is not an option.
+    Operand,
+}
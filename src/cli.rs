@@ -0,0 +1,554 @@
+//! Boilerplate shared by command-line programs, and the `getopt` program itself.
+
+use std::{env, ffi::OsString, io, path::Path};
+
+use crate::{
+    error::Error,
+    opt::Opt,
+    parser::Parser,
+    shell::{quote, Shell},
+    stop::StopReason,
+};
+
+/// Collect the program's arguments as a vector of `String`s.
+///
+/// Arguments that are not valid Unicode are converted lossily, with invalid sequences replaced
+/// by `U+FFFD REPLACEMENT CHARACTER`.
+pub fn args() -> Vec<String> {
+    env::args_os()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Collect the program's arguments as a vector of `String`s, failing on the first argument that
+/// is not valid Unicode.
+///
+/// The offending argument is returned as is, so that it can be reported to the user.
+///
+/// # Example
+///
+/// ```no_run
+/// use getopt::cli;
+///
+/// let args = match cli::args_checked() {
+///     Ok(args) => args,
+///     Err(arg) => {
+///         eprintln!("invalid argument: {}", arg.to_string_lossy());
+///         std::process::exit(2);
+///     },
+/// };
+/// ```
+pub fn args_checked() -> Result<Vec<String>, OsString> {
+    env::args_os().map(OsString::into_string).collect()
+}
+
+/// Return the name the program was invoked as, or `default` if it cannot be determined.
+///
+/// The name is the file stem of the first argument, so `/usr/bin/foo.exe` yields `foo`.
+pub fn name(default: &str) -> String {
+    match env::args_os().next() {
+        None => String::from(default),
+        Some(os_string) => match Path::new(&os_string).file_stem() {
+            None => String::from(default),
+            Some(os_str) => os_str.to_string_lossy().into_owned(),
+        },
+    }
+}
+
+/// What a [`run`](fn.run.html) of the `getopt` program produced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Output {
+    /// The exit status: `0` on success, `1` if the arguments did not parse, `2` on misuse.
+    pub status: i32,
+    /// Everything written to standard output.
+    pub stdout: String,
+    /// Everything written to standard error.
+    pub stderr: String,
+}
+
+/// How to run the `getopt` program, beyond its arguments.
+///
+/// Everything [`run`](fn.run.html) would otherwise learn from the process it runs in is given
+/// here instead, so that its output depends on nothing else.
+/// New settings may be added in future releases, so a `Settings` is made with `default` and then
+/// changed as needed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Settings {
+    /// Highlight the offending option in error messages with terminal escape sequences.
+    ///
+    /// This is usually only wanted when standard error is a terminal, and `NO_COLOR` is not set.
+    pub color: bool,
+
+    /// The optstring to use when `-o` is not given, which is then not taken from the arguments.
+    ///
+    /// It is only used when the program's own options are ended by `--`, as in
+    /// `getopt -- "$@"`; otherwise the optstring is the first argument, as usual, so that
+    /// existing invocations keep their meaning, and arguments like `-c` are never mistaken for
+    /// the program's own options.
+    ///
+    /// The `getopt` binary sets this from the `GETOPT_OPTSTRING` environment variable.
+    pub optstring: Option<String>,
+}
+
+// Command-line program boilerplate
+mod program {
+    use std::io;
+
+    pub use self::Result::*;
+
+    pub enum Result {
+        Ok(i32),
+        External(io::Error),
+        Internal(io::Error),
+    }
+}
+
+enum Format {
+    Bash,
+    Shell,
+    Tsv,
+    Zsh,
+}
+
+/// Run the `getopt` program over `args`, as if it had been invoked with them.
+///
+/// `args[0]` is the name the program was invoked as; its file stem is used in messages.
+/// Nothing is printed, the process is not exited, and its environment is not consulted, so this
+/// can be embedded in a multi-call binary, or used to drive the program from tests; `settings`
+/// stands in for the environment.
+///
+/// # Example
+///
+/// ```
+/// use getopt::cli;
+///
+/// // args = ["getopt", "ab:", "-ab", "it's", "c"];
+/// # let args: Vec<String> = vec!["getopt", "ab:", "-ab", "it's", "c"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let output = cli::run(&args, &cli::Settings::default());
+///
+/// assert_eq!(0, output.status);
+/// assert_eq!("-a -b 'it'\\''s' -- 'c'\n", output.stdout);
+/// ```
+pub fn run(args: &[String], settings: &Settings) -> Output {
+    let name = match args.first().and_then(|a| Path::new(a).file_stem()) {
+        None => String::from("getopt"),
+        Some(os_str) => os_str.to_string_lossy().into_owned(),
+    };
+    let mut stdout = String::new();
+
+    let (status, stderr) = match program(args, &name, settings, &mut stdout) {
+        program::Ok(code) => (code, String::new()),
+        program::External(error) => (1, format!("{}\n", error)),
+        program::Internal(error) => (2, format!("{}\n", error)),
+    };
+
+    Output {
+        status,
+        stdout,
+        stderr,
+    }
+}
+
+#[rustfmt::skip]
+fn usage(program_name: &str) -> String {
+    let mut usage = format!("Usage: {} [-acehp] [-f format] [-n name] [-o string] [-s shell] optstring [args ...]\n", program_name);
+    usage.push_str("  -a        use ANSI-C $'...' quoting where needed (bash, ksh, zsh)\n");
+    usage.push_str("  -c        only check the arguments: exit 0 if they are valid, 1 if not\n");
+    usage.push_str("  -e        explain how each argument was interpreted, instead of quoting\n");
+    usage.push_str("  -f format output format: 'shell' (default), 'bash', 'zsh' or 'tsv'\n");
+    usage.push_str(&format!("  -n name   report errors as 'name' (default '{}')\n", program_name));
+    usage.push_str("  -o string use string as the optstring, which is then not given as an argument\n");
+    usage.push_str("            (default: $GETOPT_OPTSTRING, if set and the options above end with '--')\n");
+    usage.push_str("  -p        only output '--' where the input had one\n");
+    usage.push_str("  -s shell  use quoting conventions for shell (default 'sh')\n");
+    usage.push('\n');
+    usage.push_str("  -h        display this help\n");
+    usage
+}
+
+fn program(args: &[String], name: &str, settings: &Settings, out: &mut String) -> program::Result {
+    let mut parsed: Vec<String> = Vec::new();
+    // for bash and zsh output: each option with its last value, or the number of times it was given
+    let mut values: Vec<(char, usize, Option<String>)> = Vec::new();
+
+    let mut child_name = name.to_string();
+    let mut ansi = false;
+    let mut check = false;
+    let mut explain = false;
+    let mut format = Format::Shell;
+    let mut optstring = None;
+    let mut preserve = false;
+    let mut shell = Shell::Bourne;
+
+    // gather our own options
+    let mut opts = Parser::new(args, "acef:hn:o:ps:");
+    opts.set_program_name(name);
+    opts.set_help_option('h');
+    loop {
+        match opts.next() {
+            None => break,
+            Some(Err(error)) => {
+                return program::Internal(usage_error(&error));
+            },
+            Some(Ok(opt)) => match opt {
+                Opt('a', None) => ansi = true,
+                Opt('c', None) => check = true,
+                Opt('e', None) => explain = true,
+                Opt('f', Some(arg)) => {
+                    format = match arg.to_lowercase().trim() {
+                        "bash" => Format::Bash,
+                        "shell" => Format::Shell,
+                        "tsv" => Format::Tsv,
+                        "zsh" => Format::Zsh,
+                        x => {
+                            return program::Internal(io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!("{}: unknown output format: {}", name, x),
+                            ));
+                        },
+                    }
+                },
+                Opt('n', Some(arg)) => child_name = arg,
+                Opt('o', Some(arg)) => optstring = Some(arg),
+                Opt('p', None) => preserve = true,
+                Opt('s', Some(arg)) => {
+                    shell = match arg.to_lowercase().trim() {
+                        "ash" | "bash" | "dash" | "ksh" | "mksh" | "sh" | "zsh" => Shell::Bourne,
+                        "csh" | "tcsh" => Shell::C,
+                        "fish" => Shell::Fish,
+                        "plan9" | "rc" => Shell::Rc,
+                        x => {
+                            return program::Internal(io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!("{}: unknown shell type: {}", name, x),
+                            ));
+                        },
+                    }
+                },
+                Opt('h', None) => {
+                    out.push_str(&usage(name));
+                    return program::Ok(0);
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    match (&format, shell) {
+        (&Format::Bash, Shell::Bourne) | (&Format::Zsh, Shell::Bourne) => (),
+        (&Format::Bash, _) | (&Format::Zsh, _) => {
+            return program::Internal(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: bash and zsh output require a Bourne-style shell", name),
+            ));
+        },
+        _ => (),
+    }
+
+    if ansi {
+        shell = match shell {
+            Shell::Bourne => Shell::AnsiC,
+            _ => {
+                return program::Internal(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{}: ANSI-C quoting requires a Bourne-style shell", name),
+                ));
+            },
+        }
+    }
+
+    // a default optstring could be mistaken for our own options, or our own for arguments to
+    // parse, unless the two are kept apart by "--"
+    if opts.stop_reason() == Some(StopReason::Separator) {
+        optstring = optstring.or_else(|| settings.optstring.clone());
+    }
+
+    let (optstring, index) = match (optstring, args.get(opts.index())) {
+        (Some(s), _) => (s, opts.index()),
+        (None, Some(s)) => (s.clone(), opts.index() + 1),
+        (None, None) => {
+            return program::Internal(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: missing optstring argument", name),
+            ));
+        },
+    };
+
+    if explain && !check {
+        return explain_args(args, &optstring, index, &child_name, out);
+    }
+
+    // parse the other options
+    let mut opts = Parser::new(args, &optstring);
+    opts.set_index(index);
+    opts.set_program_name(&child_name);
+    let separated = loop {
+        let start = opts.index();
+        let (at, point) = opts.position();
+        match opts.next() {
+            // the index only moves on a terminating "--"
+            None => break opts.index() != start,
+            Some(Err(error)) => {
+                if !settings.color {
+                    return program::External(usage_error(&error));
+                }
+                // an option character never sits at offset 0, which belongs to the leading '-'
+                let context = highlight(&child_name, &args[index..], at - index, point.max(1));
+                return program::External(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{:#}\n{}", error, context),
+                ));
+            },
+            // only errors matter when checking
+            Some(Ok(_)) if check => (),
+            Some(Ok(Opt(opt, arg))) => match format {
+                Format::Bash | Format::Zsh => match values.iter_mut().find(|v| v.0 == opt) {
+                    None => values.push((opt, 1, arg)),
+                    Some(entry) => {
+                        entry.1 += 1;
+                        entry.2 = arg;
+                    },
+                },
+                Format::Shell => {
+                    parsed.push(format!("-{}", opt));
+                    match arg {
+                        None => (),
+                        Some(s) => match quote_checked(&s, shell, name) {
+                            Ok(s) => parsed.push(s),
+                            Err(error) => return program::External(error),
+                        },
+                    }
+                },
+                Format::Tsv => match arg {
+                    None => parsed.push(format!("{}\t\tflag", opt)),
+                    Some(s) => parsed.push(format!("{}\t{}\targument", opt, escape_tsv(&s))),
+                },
+            },
+        }
+    };
+
+    if check {
+        return program::Ok(0);
+    }
+
+    match format {
+        // a global associative array of the options, so that it outlives a function that evals
+        // it, with the operands left as positional parameters; bash needs 4.2 for `declare -g`
+        Format::Bash | Format::Zsh => {
+            let zsh = match format {
+                Format::Zsh => true,
+                _ => false,
+            };
+            let mut entries = Vec::new();
+            for (opt, count, arg) in values {
+                // zparseopts -A keys options by their name, dash included, and leaves flags empty
+                let key = if zsh {
+                    format!("-{}", opt)
+                } else {
+                    opt.to_string()
+                };
+                let key = match quote_checked(&key, shell, name) {
+                    Ok(s) => s,
+                    Err(error) => return program::External(error),
+                };
+                let value = match arg {
+                    None if zsh => "''".to_string(),
+                    None => count.to_string(),
+                    Some(s) => match quote_checked(&s, shell, name) {
+                        Ok(s) => s,
+                        Err(error) => return program::External(error),
+                    },
+                };
+                if zsh {
+                    entries.push(format!("{} {}", key, value));
+                } else {
+                    entries.push(format!("[{}]={}", key, value));
+                }
+            }
+            if zsh {
+                out.push_str(&format!("typeset -gA opts\nopts=({})\n", entries.join(" ")));
+            } else {
+                out.push_str(&format!("declare -gA opts=({})\n", entries.join(" ")));
+            }
+
+            parsed.push("set --".to_string());
+            for arg in &args[opts.index()..] {
+                match quote_checked(arg, shell, name) {
+                    Ok(s) => parsed.push(s),
+                    Err(error) => return program::External(error),
+                }
+            }
+            out.push_str(&parsed.join(" "));
+            out.push('\n');
+        },
+        Format::Shell => {
+            if separated || !preserve {
+                parsed.push("--".to_string());
+            }
+
+            for arg in &args[opts.index()..] {
+                match quote_checked(arg, shell, name) {
+                    Ok(s) => parsed.push(s),
+                    Err(error) => return program::External(error),
+                }
+            }
+
+            out.push_str(&parsed.join(" "));
+            out.push('\n');
+        },
+        // one row per option or operand: option character, value, kind
+        Format::Tsv => {
+            for arg in &args[opts.index()..] {
+                parsed.push(format!("\t{}\toperand", escape_tsv(arg)));
+            }
+
+            for row in parsed {
+                out.push_str(&row);
+                out.push('\n');
+            }
+        },
+    }
+
+    program::Ok(0)
+}
+
+fn explain_args(
+    args: &[String],
+    optstring: &str,
+    index: usize,
+    name: &str,
+    out: &mut String,
+) -> program::Result {
+    let mut rows: Vec<(usize, String)> = Vec::new();
+    let mut failed = false;
+
+    let mut opts = Parser::new(args, optstring);
+    opts.set_index(index);
+    opts.set_program_name(name);
+    loop {
+        let start = opts.index();
+        match opts.next() {
+            None => {
+                // the index only moves on a terminating "--"
+                if opts.index() != start {
+                    rows.push((start, "separator".to_string()));
+                }
+                break;
+            },
+            Some(Err(error)) => {
+                failed = true;
+                rows.push((start, format!("error: {:#}", error)));
+            },
+            Some(Ok(Opt(opt, None))) => rows.push((start, format!("option {:?}", opt))),
+            Some(Ok(Opt(opt, Some(arg)))) => {
+                // a separate value consumes the following element as well
+                if opts.index() - start == 2 {
+                    rows.push((start, format!("option {:?}", opt)));
+                    rows.push((start + 1, format!("separate value for {:?}", opt)));
+                } else {
+                    rows.push((
+                        start,
+                        format!("option {:?} with attached value {:?}", opt, arg),
+                    ));
+                }
+            },
+        }
+    }
+
+    for i in opts.index()..args.len() {
+        rows.push((i, "operand".to_string()));
+    }
+
+    let tokens: Vec<String> = rows
+        .iter()
+        .map(|&(i, _)| format!("{:?}", args[i]))
+        .collect();
+    let width = tokens.iter().map(|t| t.chars().count()).max().unwrap_or(0);
+    for (&(i, ref what), token) in rows.iter().zip(tokens.iter()) {
+        // number the arguments the way the calling script sees them ($1, $2, ...)
+        out.push_str(&format!(
+            "{:>3}  {:<width$}  {}\n",
+            i + 1 - index,
+            token,
+            what,
+            width = width
+        ));
+    }
+
+    program::Ok(if failed { 1 } else { 0 })
+}
+
+// Backslash-escape anything that would break the row and column structure of the output.
+fn escape_tsv(string: &str) -> String {
+    let mut new_string = String::new();
+    for c in string.chars() {
+        match c {
+            '\\' => new_string.push_str("\\\\"),
+            '\n' => new_string.push_str("\\n"),
+            '\r' => new_string.push_str("\\r"),
+            '\t' => new_string.push_str("\\t"),
+            _ => new_string.push(c),
+        }
+    }
+    new_string
+}
+
+// getopt(1) reports errors in the traditional wording, without naming the argument.
+fn usage_error(error: &Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{:#}", error))
+}
+
+// Render the command line with the character at `args[arg][point]` marked, and a caret under it.
+fn highlight(name: &str, args: &[String], arg: usize, point: usize) -> String {
+    let mut line = format!("  {}", name);
+    let mut column = 0;
+    for (i, a) in args.iter().enumerate() {
+        line.push(' ');
+        if i != arg {
+            line.push_str(a);
+            continue;
+        }
+        column = line.chars().count() + point;
+        for (j, c) in a.chars().enumerate() {
+            if j == point {
+                line.push_str(&format!("\x1b[1;4;31m{}\x1b[0m", c));
+            } else {
+                line.push(c);
+            }
+        }
+    }
+    format!("{}\n{}\x1b[1;31m^\x1b[0m", line, " ".repeat(column))
+}
+
+// Quote a string, refusing characters that cannot be passed through the shell's quoting intact.
+// Plain quoting keeps control characters verbatim, where they are easily mangled by anything that
+// reads the output a line at a time; a NUL cannot be represented at all.
+// ANSI-C quoting escapes every other control character, the C1 controls included.
+// A refused argument is bad input rather than misuse, so it is reported as an external error.
+fn quote_checked(string: &str, shell: Shell, name: &str) -> Result<String, io::Error> {
+    let bad = string.chars().find(|&c| match shell {
+        Shell::AnsiC => c == '\0',
+        _ => c.is_control() && c != '\t',
+    });
+
+    match bad {
+        None => Ok(quote(string, shell)),
+        Some(c) => {
+            let hint = match shell {
+                Shell::Bourne => "; use -a for ANSI-C quoting",
+                _ => "",
+            };
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: argument contains a character that cannot be quoted safely -- {:?}{}",
+                    name, c, hint
+                ),
+            ))
+        },
+    }
+}
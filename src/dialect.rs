@@ -0,0 +1,25 @@
+/// Which wording [`Error`](struct.Error.html) uses for the errors C `getopt` also reports.
+///
+/// Programs ported from C can match the messages of the original, e.g. to compare their output
+/// in tests.
+/// Only the messages for unknown options and missing option-arguments differ between dialects;
+/// the other errors have no counterpart in C `getopt`, and are worded the same in every dialect.
+///
+/// New dialects may be added in future releases, so matches against this enum must include a
+/// wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// This crate's own wording: `unknown option -- 'x'`.
+    Getopt,
+    /// The wording of the GNU C Library: `invalid option -- 'x'`.
+    Glibc,
+    /// The wording of the BSD C libraries: `illegal option -- x`, with the option unquoted.
+    Bsd,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect::Getopt
+    }
+}
@@ -0,0 +1,29 @@
+//! Helpers for taking arguments from the environment.
+
+use std::env;
+
+use crate::{result::Result, shell};
+
+/// Read the environment variable `name` and split it into arguments, honouring quotes and
+/// backslashes as described for [`shell::split`](../shell/fn.split.html).
+///
+/// An unset variable yields no arguments.
+/// A value that is not valid Unicode is converted lossily, with invalid sequences replaced by
+/// `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// std::env::set_var("TOOL_FLAGS", r#"-a 'b c' -d"e f""#);
+///
+/// assert_eq!(vec!["-a", "b c", "-de f"], getopt::env::split_var("TOOL_FLAGS")?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split_var(name: &str) -> Result<Vec<String>> {
+    match env::var_os(name) {
+        None => Ok(Vec::new()),
+        Some(value) => shell::split(&value.to_string_lossy()),
+    }
+}
@@ -1,37 +1,341 @@
-use std::{error, fmt};
+use std::{error, fmt, io};
 
-use crate::ErrorKind::{self, *};
+use crate::{
+    dialect::Dialect,
+    ErrorKind::{self, *},
+};
 
 /// A basic error type for [`Parser`](struct.Parser.html)
-#[derive(Debug, Eq, PartialEq)]
+///
+/// When the argument the error was found in is known, the message names it, e.g.
+/// `unknown option -- 'q' in '-xqf' (argument 3)`.
+/// The alternate form of `Display` (`{:#}`) leaves it out, for the traditional wording of
+/// `getopt(3)`: `unknown option -- 'q'`.
+/// So does any [`Dialect`](enum.Dialect.html) other than the default, as those reproduce another
+/// implementation's messages.
+///
+/// Two errors are equal if they agree in everything but their [`source`](#method.source),
+/// which cannot itself be compared.
+#[derive(Debug)]
 pub struct Error {
     culprit: char,
     kind: ErrorKind,
+    // boxed, so that results carrying an error stay small
+    context: Box<Context>,
+}
+
+#[derive(Debug, Default)]
+struct Context {
+    dialect: Dialect,
+    hint: Option<String>,
+    program_name: Option<String>,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
+    token: Option<(String, usize)>,
+    usage: Option<String>,
+    value: Option<String>,
 }
 
 impl Error {
     /// Creates a new error using a known kind and the character that caused the issue.
     pub fn new(kind: ErrorKind, culprit: char) -> Self {
-        Self { culprit, kind }
+        Self {
+            culprit,
+            kind,
+            context: Box::default(),
+        }
+    }
+
+    /// Creates a new error of kind [`InvalidValue`](enum.ErrorKind.html#variant.InvalidValue),
+    /// for the option `culprit` given the unacceptable `value`.
+    ///
+    /// `source` is the reason the value was rejected; it may be another error, or simply a
+    /// message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Error, ErrorKind};
+    ///
+    /// let source = "abc".parse::<u32>().unwrap_err();
+    /// let error = Error::invalid_value('n', "abc", source);
+    ///
+    /// assert_eq!(ErrorKind::InvalidValue, error.kind());
+    /// assert_eq!(Some("abc"), error.value());
+    /// assert_eq!(
+    ///     "invalid value \"abc\" for option -- 'n': invalid digit found in string",
+    ///     error.to_string()
+    /// );
+    ///
+    /// let error = Error::invalid_value('m', "x", "expected a mode");
+    /// assert_eq!(
+    ///     "invalid value \"x\" for option -- 'm': expected a mode",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn invalid_value<E>(culprit: char, value: &str, source: E) -> Self
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        let mut error = Self::new(InvalidValue, culprit);
+        error.context.source = Some(source.into());
+        error.context.value = Some(value.to_string());
+        error
+    }
+
+    // An error of kind `LimitExceeded`; the culprit is `-` unless one option is at fault.
+    pub(crate) fn limit_exceeded(culprit: char, message: String) -> Self {
+        let mut error = Self::new(LimitExceeded, culprit);
+        error.context.source = Some(message.into());
+        error
+    }
+
+    // An error of kind `InvalidUnicode`, for the argument at `index`.
+    pub(crate) fn invalid_unicode(arg: &str, index: usize) -> Self {
+        let mut error = Self::new(InvalidUnicode, '-');
+        error.set_token(arg, index);
+        error
+    }
+
+    /// Returns the option character that caused this error.
+    pub fn culprit(&self) -> char {
+        self.culprit
     }
 
     /// Returns the [`ErrorKind`](enum.ErrorKind.html) for this error.
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+
+    /// Returns the offending value, for errors of kind
+    /// [`InvalidValue`](enum.ErrorKind.html#variant.InvalidValue).
+    pub fn value(&self) -> Option<&str> {
+        self.context.value.as_deref()
+    }
+
+    /// Returns the argument in which the error was found, if known.
+    pub fn token(&self) -> Option<&str> {
+        self.context.token.as_ref().map(|(token, _)| token.as_str())
+    }
+
+    /// Returns the index of the argument in which the error was found, if known.
+    pub fn index(&self) -> Option<usize> {
+        self.context.token.as_ref().map(|&(_, index)| index)
+    }
+
+    /// Record the argument in which the error was found, and its index in the argument vector.
+    ///
+    /// [`Parser`](struct.Parser.html) does this for every error it returns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-a", "-xqf"];
+    /// # let args: Vec<String> = vec!["program", "-a", "-xqf"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "afx");
+    /// opts.next();
+    /// opts.next();
+    ///
+    /// let error = opts.next().unwrap().unwrap_err();
+    /// assert_eq!((Some("-xqf"), Some(2)), (error.token(), error.index()));
+    /// assert_eq!("unknown option -- 'q' in '-xqf' (argument 2)", format!("{}", error));
+    /// assert_eq!("unknown option -- 'q'", format!("{:#}", error));
+    /// ```
+    pub fn set_token(&mut self, token: &str, index: usize) {
+        self.context.token = Some((token.to_string(), index));
+    }
+
+    /// Returns the usage synopsis shown with this error, if one has been set.
+    pub fn usage(&self) -> Option<&str> {
+        self.context.usage.as_deref()
+    }
+
+    /// Set a usage synopsis to show with this error.
+    ///
+    /// The synopsis is displayed on the lines following the error message, e.g.:
+    ///
+    /// ```text
+    /// prog: unknown option -- 'x'
+    /// usage: prog [-ab] file ...
+    /// ```
+    ///
+    /// A trailing newline in `usage` is ignored.
+    pub fn set_usage(&mut self, usage: &str) {
+        self.context.usage = Some(usage.trim_end_matches('\n').to_string());
+    }
+
+    /// Returns the [`Dialect`](enum.Dialect.html) this error is worded in.
+    pub fn dialect(&self) -> Dialect {
+        self.context.dialect
+    }
+
+    /// Set the [`Dialect`](enum.Dialect.html) this error is worded in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Dialect, Error, ErrorKind};
+    ///
+    /// let mut error = Error::new(ErrorKind::UnknownOption, 'x');
+    /// assert_eq!("unknown option -- 'x'", error.to_string());
+    /// error.set_dialect(Dialect::Glibc);
+    /// assert_eq!("invalid option -- 'x'", error.to_string());
+    /// error.set_dialect(Dialect::Bsd);
+    /// assert_eq!("illegal option -- x", error.to_string());
+    ///
+    /// let mut error = Error::new(ErrorKind::MissingArgument, 'o');
+    /// error.set_dialect(Dialect::Bsd);
+    /// assert_eq!("option requires an argument -- o", error.to_string());
+    /// ```
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.context.dialect = dialect;
+    }
+
+    /// Returns the hint shown with this error, if one has been set.
+    pub fn hint(&self) -> Option<&str> {
+        self.context.hint.as_deref()
+    }
+
+    /// Set a hint to show with this error, such as `Try 'prog -h' for more information.`
+    ///
+    /// The hint is displayed on the last line, after any usage synopsis.
+    /// [`Parser::set_help_option`](struct.Parser.html#method.set_help_option) sets the usual
+    /// hint for every error.
+    pub fn set_hint(&mut self, hint: &str) {
+        self.context.hint = Some(hint.trim_end_matches('\n').to_string());
+    }
+
+    /// Returns the conventional exit status for a program that fails because of this error.
+    ///
+    /// Errors in command-line usage are traditionally reported with an exit status of `2`.
+    pub fn exit_code(&self) -> i32 {
+        2
+    }
+
+    /// Returns the name of the program this error is reported for, if one has been set.
+    pub fn program_name(&self) -> Option<&str> {
+        self.context.program_name.as_deref()
+    }
+
+    /// Set the name of the program this error is reported for.
+    ///
+    /// When a name is set, it is prefixed to the error message in the conventional manner:
+    /// `name: unknown option -- 'x'`.
+    pub fn set_program_name(&mut self, name: &str) {
+        self.context.program_name = Some(name.to_string());
+    }
+}
+
+impl Error {
+    // Name the argument the error was found in, unless the traditional wording is wanted.
+    fn fmt_token(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.context.token, self.context.dialect) {
+            (&Some((ref token, index)), Dialect::Getopt) if !f.alternate() => {
+                write!(f, " in '{}' (argument {})", token, index)
+            },
+            _ => Ok(()),
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref name) = self.context.program_name {
+            write!(f, "{}: ", name)?;
+        }
+
+        match (self.kind, self.context.dialect) {
+            (MissingArgument, Dialect::Bsd) => {
+                write!(f, "option requires an argument -- {}", self.culprit)
+            },
+            (MissingArgument, _) => {
+                write!(f, "option requires an argument -- {:?}", self.culprit)
+            },
+            (UnknownOption, Dialect::Glibc) => write!(f, "invalid option -- {:?}", self.culprit),
+            (UnknownOption, Dialect::Bsd) => write!(f, "illegal option -- {}", self.culprit),
+            (UnknownOption, _) => write!(f, "unknown option -- {:?}", self.culprit),
+            (UnexpectedArgument, _) => {
+                write!(f, "option does not take an argument -- {:?}", self.culprit)
+            },
+            (OutOfOrder, _) => write!(f, "option out of order -- {:?}", self.culprit),
+            (TooManyOccurrences, _) => {
+                write!(f, "option given too many times -- {:?}", self.culprit)
+            },
+            (UnmatchedQuote, _) => write!(f, "unmatched quote -- {:?}", self.culprit),
+            (InvalidValue, _) => {
+                write!(
+                    f,
+                    "invalid value {:?} for option -- {:?}",
+                    self.context.value.as_deref().unwrap_or(""),
+                    self.culprit
+                )?;
+                // the reason comes last, as it may be long
+                self.fmt_token(f)?;
+                match self.context.source {
+                    None => Ok(()),
+                    Some(ref source) => write!(f, ": {}", source),
+                }
+            },
+            (LimitExceeded, _) => match self.context.source {
+                None => write!(f, "input limit exceeded"),
+                Some(ref source) => write!(f, "input limit exceeded: {}", source),
+            },
+            (InvalidUnicode, _) => match self.context.token {
+                None => write!(f, "argument is not valid Unicode"),
+                Some((_, index)) => write!(f, "argument {} is not valid Unicode", index),
+            },
+        }?;
+
+        // invalid Unicode names its argument already, and cannot show it faithfully
         match self.kind {
-            MissingArgument => write!(f, "option requires an argument -- {:?}", self.culprit),
-            UnknownOption => write!(f, "unknown option -- {:?}", self.culprit),
+            InvalidValue | InvalidUnicode => (),
+            _ => self.fmt_token(f)?,
+        }
+
+        if let Some(ref usage) = self.context.usage {
+            write!(f, "\n{}", usage)?;
         }
+
+        if let Some(ref hint) = self.context.hint {
+            write!(f, "\n{}", hint)?;
+        }
+
+        Ok(())
     }
 }
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        None
+        match self.context.source {
+            None => None,
+            Some(ref source) => Some(&**source),
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.culprit == other.culprit
+            && self.kind == other.kind
+            && self.context.dialect == other.context.dialect
+            && self.context.hint == other.context.hint
+            && self.context.program_name == other.context.program_name
+            && self.context.token == other.context.token
+            && self.context.usage == other.context.usage
+            && self.context.value == other.context.value
+    }
+}
+
+impl Eq for Error {}
+
+impl From<Error> for io::Error {
+    /// Converts the error into an [`io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html)
+    /// of kind `InvalidInput`.
+    fn from(error: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, error)
     }
 }
@@ -1,8 +1,50 @@
 /// What kinds of errors [`Parser`](struct.Parser.html) can return.
+///
+/// New kinds may be added in future releases, so matches against this enum must include a
+/// wildcard arm.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// An argument was not found for an option that was expecting one.
     MissingArgument,
     /// An unknown option character was encountered.
     UnknownOption,
+    /// An argument was given to an option that does not take one.
+    UnexpectedArgument,
+    /// An option was given where the order of options forbids it.
+    OutOfOrder,
+    /// An option was given more times than it may be.
+    TooManyOccurrences,
+    /// A quote was opened but never closed while splitting a string into arguments.
+    UnmatchedQuote,
+    /// An option-argument was given, but its value was not acceptable.
+    InvalidValue,
+    /// The arguments exceeded a limit set on the parser, such as the number of arguments.
+    LimitExceeded,
+    /// An argument was not valid Unicode.
+    InvalidUnicode,
+}
+
+impl ErrorKind {
+    /// Returns the character C `getopt` would return for an error of this kind.
+    ///
+    /// C `getopt` returns `'?'` for every error, except that it returns `':'` for a missing
+    /// option-argument when the optstring begins with a colon; `leading_colon` selects that
+    /// behaviour.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::ErrorKind;
+    ///
+    /// assert_eq!('?', ErrorKind::UnknownOption.as_posix_char(true));
+    /// assert_eq!('?', ErrorKind::MissingArgument.as_posix_char(false));
+    /// assert_eq!(':', ErrorKind::MissingArgument.as_posix_char(true));
+    /// ```
+    pub fn as_posix_char(self, leading_colon: bool) -> char {
+        match self {
+            ErrorKind::MissingArgument if leading_colon => ':',
+            _ => '?',
+        }
+    }
 }
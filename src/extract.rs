@@ -0,0 +1,94 @@
+use crate::{
+    error::Error, errorkind::ErrorKind, opt::Opt, parser::parse_optstring, result::Result,
+};
+
+/// Extract the options specified in `optstring` from `args`, leaving everything else in place.
+///
+/// Returns the extracted options, and `args` with them removed.
+/// The first element of `args` is taken to be the program name, and is always kept.
+///
+/// Options are only looked for up to the first operand or `--`; from there on, `args` is kept
+/// untouched.
+/// Elements containing only unknown options are kept byte-for-byte.
+/// An element mixing known and unknown options, like `-av` where only `a` is known, keeps the
+/// unknown remainder (`-v`); everything from the first unknown option onwards is kept, as it
+/// may be that option's argument.
+///
+/// Since unknown options cannot be known to take an argument, such an argument in the
+/// following element is taken as an operand, which stops the search.
+///
+/// Returns an [`Error`](struct.Error.html) if a known option is missing its argument.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use getopt::Opt;
+///
+/// // args = ["wrapper", "-v", "-x", "-Wfoo", "-av", "file", "-W", "bar"];
+/// # let args: Vec<String> = vec!["wrapper", "-v", "-x", "-Wfoo", "-av", "file", "-W", "bar"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let (opts, rest) = getopt::extract(&args, "W:a")?;
+///
+/// assert_eq!(vec![Opt('W', Some("foo".to_string())), Opt('a', None)], opts);
+/// assert_eq!(vec!["wrapper", "-v", "-x", "-v", "file", "-W", "bar"], rest);
+/// # Ok(())
+/// # }
+/// ```
+pub fn extract(args: &[String], optstring: &str) -> Result<(Vec<Opt>, Vec<String>)> {
+    let known = parse_optstring(optstring);
+    let mut opts = Vec::new();
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+
+    if let Some(name) = iter.next() {
+        rest.push(name.clone());
+    }
+
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with('-') || arg == "-" || arg == "--" {
+            rest.push(arg.clone());
+            break;
+        }
+
+        let mut chars = arg[1..].chars();
+        loop {
+            let unparsed = chars.as_str();
+            let opt = match chars.next() {
+                None => break,
+                Some(opt) => opt,
+            };
+
+            match known.get(&opt) {
+                None => {
+                    if unparsed.len() + 1 == arg.len() {
+                        rest.push(arg.clone());
+                    } else {
+                        rest.push(format!("-{}", unparsed));
+                    }
+                    break;
+                },
+                Some(false) => opts.push(Opt(opt, None)),
+                Some(true) => {
+                    let attached = chars.as_str();
+                    let value = if attached.is_empty() {
+                        match iter.next() {
+                            None => return Err(Error::new(ErrorKind::MissingArgument, opt)),
+                            Some(value) => value.clone(),
+                        }
+                    } else {
+                        attached.to_string()
+                    };
+                    opts.push(Opt(opt, Some(value)));
+                    break;
+                },
+            }
+        }
+    }
+
+    rest.extend(iter.cloned());
+
+    Ok((opts, rest))
+}
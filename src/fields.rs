@@ -0,0 +1,43 @@
+use crate::opt::Opt;
+
+/// Render options as key/value fields, for structured logging.
+///
+/// Each option gives one field, in the order given: the key is the option as it is written, as
+/// in `-o`, and the value is its argument, or `true` for an option without one.
+/// The arguments of the options in `secret` are replaced by `[redacted]`, so that passwords and
+/// tokens given on the command line are not written to logs.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use getopt::Parser;
+///
+/// // args = ["program", "-v", "-u", "admin", "-p", "hunter2"];
+/// # let args: Vec<String> = vec!["program", "-v", "-u", "admin", "-p", "hunter2"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let (opts, _) = Parser::new(&args, "p:u:v").into_parts()?;
+/// let fields = getopt::log_fields(&opts, "p");
+///
+/// let fields: Vec<_> = fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+/// assert_eq!(vec![("-v", "true"), ("-u", "admin"), ("-p", "[redacted]")], fields);
+/// # Ok(())
+/// # }
+/// ```
+pub fn log_fields<'a, I>(opts: I, secret: &str) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = &'a Opt>,
+{
+    opts.into_iter()
+        .map(|opt| {
+            let value = match opt.1 {
+                None => "true".to_string(),
+                Some(_) if secret.contains(opt.0) => "[redacted]".to_string(),
+                Some(ref arg) => arg.clone(),
+            };
+            (format!("-{}", opt.0), value)
+        })
+        .collect()
+}
@@ -0,0 +1,25 @@
+/// Utility Syntax Guidelines whose behaviour [`Parser`](struct.Parser.html) can toggle.
+///
+/// The guidelines are those of "The Open Group Base Specifications Issue 7, 2018 edition",
+/// section 12.2.
+/// By default, every guideline listed here is followed.
+///
+/// Guidelines not listed here are either always followed or outside the scope of a parser.
+/// In particular, guideline 9 ("all options should precede operands") cannot be relaxed, as
+/// doing so would require reordering the argument vector behind the caller's back.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Guideline {
+    /// Guideline 5: options without option-arguments may be grouped behind one `-`.
+    ///
+    /// When disabled, an option that takes no argument must be the only character of its
+    /// element; anything following it is reported as an
+    /// [`UnexpectedArgument`](enum.ErrorKind.html#variant.UnexpectedArgument).
+    Grouping,
+    /// Guideline 10: the first `--` argument that is not an option-argument is accepted as a
+    /// delimiter indicating the end of options.
+    ///
+    /// When disabled, `--` is treated like any other operand: parsing stops without consuming
+    /// it.
+    Delimiter,
+}
@@ -0,0 +1,157 @@
+/// A lexical token, as produced by [`Lexer`](struct.Lexer.html).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Token<'a> {
+    /// One or more short options behind a single `-`, without the `-`: `-abc` yields `"abc"`.
+    ShortCluster(&'a str),
+    /// A long option, without the leading `--`, and with the value following `=`, if any.
+    LongOption(&'a str, Option<&'a str>),
+    /// The `--` argument marking the end of options.
+    Separator,
+    /// An argument which is not an option, including a lone `-`.
+    Operand(&'a str),
+    /// An operand of the form `NAME=value`, when recognised with
+    /// [`set_assignments`](struct.Lexer.html#method.set_assignments).
+    Assignment(&'a str, &'a str),
+}
+
+/// An iterator classifying arguments by their form alone, without reference to any optstring.
+///
+/// `Lexer` exposes the lexical structure of a command line for tools which analyse it without
+/// knowing which options are valid.
+/// As option-arguments cannot be told apart from operands without an optstring, every element
+/// not starting with `-` is an operand, and lexing carries on past operands; only `--` ends
+/// option recognition.
+///
+/// The lexer starts at the first element of the slice it is given, so a program name should be
+/// left out.
+///
+/// # Example
+///
+/// ```
+/// use getopt::{Lexer, Token};
+///
+/// // args = ["program", "-ab", "--color=auto", "file", "--", "-c"];
+/// # let args: Vec<String> = vec!["program", "-ab", "--color=auto", "file", "--", "-c"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let tokens: Vec<Token> = Lexer::new(&args[1..]).collect();
+///
+/// assert_eq!(
+///     vec![
+///         Token::ShortCluster("ab"),
+///         Token::LongOption("color", Some("auto")),
+///         Token::Operand("file"),
+///         Token::Separator,
+///         Token::Operand("-c"),
+///     ],
+///     tokens
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Lexer<'a> {
+    args: &'a [String],
+    index: usize,
+    done: bool,
+    assignments: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Create a new `Lexer` over `args`.
+    pub fn new(args: &'a [String]) -> Self {
+        Self {
+            args,
+            index: 0,
+            done: false,
+            assignments: false,
+        }
+    }
+
+    /// Choose whether operands of the form `NAME=value` are returned as
+    /// [`Assignment`](enum.Token.html#variant.Assignment)s, as `make` and `dd` treat them.
+    ///
+    /// `NAME` must be a valid identifier: a letter or underscore, followed by any number of
+    /// letters, digits and underscores.
+    /// Operands following `--` are never treated as assignments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Lexer, Token};
+    ///
+    /// // args = ["program", "CC=cc", "all", "--", "X=1"];
+    /// # let args: Vec<String> = vec!["program", "CC=cc", "all", "--", "X=1"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut lexer = Lexer::new(&args[1..]);
+    /// lexer.set_assignments(true);
+    /// let tokens: Vec<Token> = lexer.collect();
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         Token::Assignment("CC", "cc"),
+    ///         Token::Operand("all"),
+    ///         Token::Separator,
+    ///         Token::Operand("X=1"),
+    ///     ],
+    ///     tokens
+    /// );
+    /// ```
+    pub fn set_assignments(&mut self, enabled: bool) {
+        self.assignments = enabled;
+    }
+}
+
+// Split `arg` into a name and value if it is of the form `NAME=value`.
+fn assignment(arg: &str) -> Option<(&str, &str)> {
+    let equals = arg.find('=')?;
+    let name = &arg[..equals];
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_alphabetic() => (),
+        _ => return None,
+    }
+    if !chars.all(|c| c == '_' || c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    Some((name, &arg[equals + 1..]))
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let arg = self.args.get(self.index)?.as_str();
+        self.index += 1;
+
+        if self.done {
+            return Some(Token::Operand(arg));
+        }
+
+        if arg == "-" || !arg.starts_with('-') {
+            if self.assignments {
+                if let Some((name, value)) = assignment(arg) {
+                    return Some(Token::Assignment(name, value));
+                }
+            }
+            return Some(Token::Operand(arg));
+        }
+
+        if arg == "--" {
+            self.done = true;
+            return Some(Token::Separator);
+        }
+
+        if arg.starts_with("--") {
+            let mut parts = arg[2..].splitn(2, '=');
+            // splitn always yields at least one part
+            let name = parts.next().unwrap_or("");
+            return Some(Token::LongOption(name, parts.next()));
+        }
+
+        Some(Token::ShortCluster(&arg[1..]))
+    }
+}
@@ -4,15 +4,66 @@
 //!
 //! `getopt` provides a minimal, (essentially) POSIX-compliant option parser.
 
-pub use crate::{error::Error, errorkind::ErrorKind, opt::Opt, parser::Parser, result::Result};
+pub use crate::{
+    adapters::{Lenient, OkOnly, Report, Strict, WithContext, WithPositions},
+    class::Class,
+    dialect::Dialect,
+    error::Error,
+    errorkind::ErrorKind,
+    extract::extract,
+    fields::log_fields,
+    guideline::Guideline,
+    lexer::{Lexer, Token},
+    lint::{lint, Lint},
+    located::Located,
+    opt::Opt,
+    parser::Parser,
+    prescan::prescan,
+    push::{reconstruct, Event, Layout, PushParser},
+    result::Result,
+    split::split_args,
+    stop::StopReason,
+    value::FromOptValue,
+    warning::{Warning, WarningKind},
+};
 
+#[cfg(unix)]
+pub use crate::split::split_nul;
+#[cfg(feature = "wildcards")]
+pub use crate::wildcards::expand_wildcards;
+
+mod adapters;
+mod class;
+pub mod cli;
+mod dialect;
+pub mod env;
 mod error;
 mod errorkind;
+mod extract;
+mod fields;
+mod guideline;
+mod lexer;
+mod lint;
+mod located;
+#[macro_use]
+mod macros;
 mod opt;
 mod parser;
+pub mod parsers;
+mod prescan;
+mod push;
 mod result;
+pub mod shell;
+mod split;
+mod stop;
+#[cfg(feature = "test-util")]
+mod test_util;
 #[cfg(test)]
 mod tests;
+mod value;
+mod warning;
+#[cfg(feature = "wildcards")]
+mod wildcards;
 
 // Include README.md when running doctests.
 // Credit to Guillaume Gomez (https://github.com/GuillaumeGomez/doc-comment) for the idea and
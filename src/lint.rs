@@ -0,0 +1,79 @@
+use std::fmt;
+
+/// A suspicious construct in an optstring, as reported by [`lint`](fn.lint.html).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Lint {
+    /// An option character is listed more than once; only its last listing has any effect.
+    Duplicate(char),
+    /// The optstring begins with a colon, which C `getopt` takes to request silent error
+    /// reporting, but which this crate takes as the option `-:`.
+    LeadingColon,
+    /// A colon not following an option character is itself taken as an option, as in `a::`.
+    ColonOption,
+    /// A hyphen is listed as an option; `--` can then never be a group of hyphen options.
+    HyphenOption,
+    /// An option character is outside the ASCII range, and possibly a typo.
+    NonAscii(char),
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Lint::Duplicate(c) => write!(f, "option listed more than once -- {:?}", c),
+            Lint::LeadingColon => write!(f, "leading ':' is an option, not a request for silence"),
+            Lint::ColonOption => write!(f, "':' listed as an option"),
+            Lint::HyphenOption => write!(f, "'-' listed as an option"),
+            Lint::NonAscii(c) => write!(f, "non-ASCII option -- {:?}", c),
+        }
+    }
+}
+
+/// Check `optstring` for suspicious constructs.
+///
+/// The optstring is read as by [`Parser::new`](struct.Parser.html#method.new); the constructs
+/// reported are all valid, but are likely to be mistakes.
+///
+/// # Example
+///
+/// ```
+/// use getopt::Lint;
+///
+/// assert!(getopt::lint("ab:c").is_empty());
+/// assert_eq!(
+///     vec![Lint::LeadingColon, Lint::Duplicate('a'), Lint::ColonOption, Lint::NonAscii('é')],
+///     getopt::lint(":ab:a::é")
+/// );
+/// ```
+pub fn lint(optstring: &str) -> Vec<Lint> {
+    let optstring: Vec<char> = optstring.chars().collect();
+    let mut lints = Vec::new();
+    let mut seen = Vec::new();
+    let mut i = 0;
+    let len = optstring.len();
+
+    while i < len {
+        let c = optstring[i];
+
+        match c {
+            ':' if i == 0 => lints.push(Lint::LeadingColon),
+            ':' => lints.push(Lint::ColonOption),
+            '-' => lints.push(Lint::HyphenOption),
+            c if !c.is_ascii() => lints.push(Lint::NonAscii(c)),
+            _ => (),
+        }
+        // a repeated colon has already been reported as such
+        if c != ':' && seen.contains(&c) {
+            lints.push(Lint::Duplicate(c));
+        } else {
+            seen.push(c);
+        }
+
+        if i + 1 < len && optstring[i + 1] == ':' {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    lints
+}
@@ -0,0 +1,54 @@
+use crate::opt::Opt;
+
+/// An option, together with where it was found in the arguments.
+///
+/// This `struct` is yielded by the iterator returned from
+/// [`Parser::with_positions`](struct.Parser.html#method.with_positions).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Located {
+    opt: Opt,
+    index: usize,
+    offset: usize,
+    value_index: Option<usize>,
+}
+
+impl Located {
+    pub(crate) fn new(opt: Opt, index: usize, offset: usize, value_index: Option<usize>) -> Self {
+        Self {
+            opt,
+            index,
+            offset,
+            value_index,
+        }
+    }
+
+    /// Returns the option.
+    pub fn opt(&self) -> &Opt {
+        &self.opt
+    }
+
+    /// Returns the option, discarding its position.
+    pub fn into_opt(self) -> Opt {
+        self.opt
+    }
+
+    /// Returns the index of the argument in which the option character was found.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the offset, in characters, of the option character within its argument.
+    ///
+    /// The offset is `1` for an option at the start of an argument, just after the `-`.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the index of the argument holding the option-argument, if there is one.
+    ///
+    /// This is the same as [`index`](#method.index) when the option-argument is attached to the
+    /// option, as in `-ofoo`, and the index of the following argument when it is separate.
+    pub fn value_index(&self) -> Option<usize> {
+        self.value_index
+    }
+}
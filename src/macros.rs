@@ -0,0 +1,48 @@
+/// A pattern matching an [`Opt`](struct.Opt.html) which takes no argument.
+///
+/// `flag!('a')` is equivalent to `Opt('a', None)`.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate getopt;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // args = ["program", "-a", "-bfoo"];
+/// # let args: Vec<String> = vec!["program", "-a", "-bfoo"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let mut opts = getopt::Parser::new(&args, "ab:");
+///
+/// let mut a_flag = false;
+/// let mut b_flag = String::new();
+/// while let Some(opt) = opts.next().transpose()? {
+///     match opt {
+///         flag!('a') => a_flag = true,
+///         optarg!('b', arg) => b_flag = arg,
+///         _ => unreachable!(),
+///     }
+/// }
+///
+/// assert!(a_flag);
+/// assert_eq!("foo", b_flag);
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! flag {
+    ($opt:pat) => {
+        $crate::Opt($opt, None)
+    };
+}
+
+/// A pattern matching an [`Opt`](struct.Opt.html) which takes an argument, binding the argument.
+///
+/// `optarg!('b', arg)` is equivalent to `Opt('b', Some(arg))`; see [`flag!`](macro.flag.html)
+/// for an example.
+#[macro_export]
+macro_rules! optarg {
+    ($opt:pat, $arg:pat) => {
+        $crate::Opt($opt, Some($arg))
+    };
+}
@@ -1,11 +1,16 @@
 use std::fmt;
 
+use crate::{error::Error, result::Result, value::FromOptValue};
+
 /// A single option.
 ///
 /// For `Opt(x, y)`:
 ///   - `x` is the character representing the option.
 ///   - `y` is `Some` string, or `None` if no argument was expected.
 ///
+/// The alternate form of `Display` (`{:#}`) renders the option as it would appear on a command
+/// line, e.g. `-a` or `-b c`; the argument is not quoted.
+///
 /// # Example
 ///
 /// ```
@@ -23,14 +28,128 @@ use std::fmt;
 /// assert_eq!(Opt('a', None), opts.next().transpose()?.unwrap());
 /// assert_eq!(Opt('b', Some("c".to_string())), opts.next().transpose()?.unwrap());
 /// assert_eq!(None, opts.next().transpose()?);
+///
+/// assert_eq!("-a", format!("{:#}", Opt('a', None)));
+/// assert_eq!("-b c", format!("{:#}", Opt('b', Some("c".to_string()))));
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Opt(pub char, pub Option<String>);
 
+impl Opt {
+    /// Convert the option's argument into a value of type `T`.
+    ///
+    /// Returns `None` if the option has no argument, or an [`Error`](struct.Error.html) of kind
+    /// [`InvalidValue`](enum.ErrorKind.html#variant.InvalidValue) if the argument cannot be
+    /// converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use getopt::Opt;
+    ///
+    /// assert_eq!(Some(8080), Opt('p', Some("8080".to_string())).parse_value::<u16>()?);
+    /// assert_eq!(None, Opt('v', None).parse_value::<u16>()?);
+    /// assert_eq!(
+    ///     "invalid value \"http\" for option -- 'p': invalid digit found in string",
+    ///     Opt('p', Some("http".to_string()))
+    ///         .parse_value::<u16>()
+    ///         .unwrap_err()
+    ///         .to_string()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_value<T: FromOptValue>(&self) -> Result<Option<T>> {
+        match self.1 {
+            None => Ok(None),
+            Some(ref value) => match T::from_opt_value(value) {
+                Ok(value) => Ok(Some(value)),
+                Err(source) => Err(Error::invalid_value(self.0, value, source)),
+            },
+        }
+    }
+
+    /// Take the option's argument out, leaving `None` in its place.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Opt;
+    ///
+    /// let mut opts = vec![Opt('o', Some("out.txt".to_string()))];
+    /// let mut output = None;
+    /// for opt in opts.iter_mut() {
+    ///     if opt.0 == 'o' {
+    ///         output = opt.take_value();
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Some("out.txt".to_string()), output);
+    /// assert_eq!(Opt('o', None), opts[0]);
+    /// ```
+    pub fn take_value(&mut self) -> Option<String> {
+        self.1.take()
+    }
+
+    /// Consume the option, returning its argument.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Opt;
+    ///
+    /// assert_eq!(Some("c".to_string()), Opt('b', Some("c".to_string())).into_value());
+    /// assert_eq!(None, Opt('a', None).into_value());
+    /// ```
+    pub fn into_value(self) -> Option<String> {
+        self.1
+    }
+}
+
 impl fmt::Display for Opt {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Opt({:?}, {:?})", self.0, self.1)
+        if f.alternate() {
+            match self.1 {
+                None => write!(f, "-{}", self.0),
+                Some(ref arg) => write!(f, "-{} {}", self.0, arg),
+            }
+        } else {
+            write!(f, "Opt({:?}, {:?})", self.0, self.1)
+        }
+    }
+}
+
+/// Compare with a `(char, Option<&str>)` pair, without building a `String`.
+///
+/// # Example
+///
+/// ```
+/// use getopt::Opt;
+///
+/// assert_eq!(Opt('b', Some("c".to_string())), ('b', Some("c")));
+/// assert_ne!(Opt('b', Some("c".to_string())), ('b', None));
+/// ```
+impl<'a> PartialEq<(char, Option<&'a str>)> for Opt {
+    fn eq(&self, other: &(char, Option<&'a str>)) -> bool {
+        self.0 == other.0 && self.1.as_deref() == other.1
+    }
+}
+
+/// Compare with a bare `char`, which is equal to an option with that character and no argument.
+///
+/// # Example
+///
+/// ```
+/// use getopt::Opt;
+///
+/// assert_eq!(Opt('a', None), 'a');
+/// assert_ne!(Opt('b', Some("c".to_string())), 'b');
+/// ```
+impl PartialEq<char> for Opt {
+    fn eq(&self, other: &char) -> bool {
+        self.0 == *other && self.1.is_none()
     }
 }
@@ -1,6 +1,18 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ffi::OsString, fmt, io::Write, path::Path, process};
 
-use crate::{error::Error, errorkind::ErrorKind, opt::Opt, result::Result};
+use crate::{
+    adapters::{Lenient, OkOnly, Report, Strict, WithContext, WithPositions},
+    class::Class,
+    dialect::Dialect,
+    error::Error,
+    errorkind::ErrorKind,
+    guideline::Guideline,
+    located::Located,
+    opt::Opt,
+    result::Result,
+    stop::StopReason,
+    warning::{Warning, WarningKind},
+};
 
 /// The core of the `getopt` crate.
 ///
@@ -8,6 +20,9 @@ use crate::{error::Error, errorkind::ErrorKind, opt::Opt, result::Result};
 ///
 /// The method [`next`](#method.next) does the heavy lifting.
 ///
+/// Two parsers compare equal when they are in the same state.
+/// Validators cannot be compared, so only which options have one counts, not what they do.
+///
 /// # Examples
 ///
 /// ## Simplified usage:
@@ -56,9 +71,9 @@ use crate::{error::Error, errorkind::ErrorKind, opt::Opt, result::Result};
 ///         None => break,
 ///         Some(opt) => match opt {
 ///             Opt('a', None) => a_flag = true,
-///             Opt('b', Some(arg)) => b_flag = arg.clone(),
+///             Opt('b', Some(arg)) => b_flag = arg,
 ///             Opt('c', None) => c_flag = true,
-///             Opt('d', Some(arg)) => d_flag = arg.clone(),
+///             Opt('d', Some(arg)) => d_flag = arg,
 ///             Opt('e', None) => e_flag = true,
 ///             _ => unreachable!(),
 ///         },
@@ -81,11 +96,56 @@ use crate::{error::Error, errorkind::ErrorKind, opt::Opt, result::Result};
 #[derive(Debug, Eq, PartialEq)]
 pub struct Parser {
     opts: HashMap<char, bool>,
-    args: Vec<Vec<char>>,
+    args: Vec<String>,
     index: usize,
     point: usize,
+    deprecated: HashMap<char, Option<char>>,
+    warnings: Vec<Warning>,
+    grouping: bool,
+    delimiter: bool,
+    pass_through: bool,
+    passed: Vec<String>,
+    counts: HashMap<char, usize>,
+    limits: HashMap<char, usize>,
+    before: Vec<(char, char)>,
+    first: Vec<char>,
+    validators: HashMap<char, Validator>,
+    dash_option: bool,
+    reject_option_like: bool,
+    last: usize,
+    last_point: usize,
+    program_name: Option<String>,
+    usage: Option<String>,
+    help_option: Option<char>,
+    dialect: Dialect,
+    max_args: Option<usize>,
+    max_arg_len: Option<usize>,
+    max_values: Option<usize>,
+    input_checked: bool,
+    over_limit: bool,
+    stop: Option<StopReason>,
+}
+
+type ValidatorFn = dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync;
+
+// A boxed closure is neither comparable nor printable, so validators are left out of both, to
+// keep `Parser` comparable and printable; any two validators compare equal.
+struct Validator(Box<ValidatorFn>);
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Validator")
+    }
+}
+
+impl PartialEq for Validator {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
 }
 
+impl Eq for Validator {}
+
 impl Parser {
     /// Create a new `Parser`, which will process the arguments in `args` according to the options
     /// specified in `optstring`.
@@ -104,31 +164,95 @@ impl Parser {
     /// responsibility of the calling program, as it involves some level of potential information
     /// loss (which this crate does not presume to handle unilaterally) and error handling (which
     /// would complicate the interface).
+    /// [`cli::args`](cli/fn.args.html) and [`cli::args_checked`](cli/fn.args_checked.html)
+    /// offer the two usual answers.
     pub fn new(args: &[String], optstring: &str) -> Self {
-        let optstring: Vec<char> = optstring.chars().collect();
-        let mut opts = HashMap::new();
-        let mut i = 0;
-        let len = optstring.len();
+        Self {
+            opts: parse_optstring(optstring),
+            args: args.to_vec(),
+            index: 1,
+            point: 0,
+            deprecated: HashMap::new(),
+            warnings: Vec::new(),
+            grouping: true,
+            delimiter: true,
+            pass_through: false,
+            passed: Vec::new(),
+            counts: HashMap::new(),
+            limits: HashMap::new(),
+            before: Vec::new(),
+            first: Vec::new(),
+            validators: HashMap::new(),
+            dash_option: false,
+            reject_option_like: false,
+            last: 0,
+            last_point: 0,
+            program_name: None,
+            usage: None,
+            help_option: None,
+            dialect: Dialect::default(),
+            max_args: None,
+            max_arg_len: None,
+            max_values: None,
+            input_checked: false,
+            over_limit: false,
+            stop: None,
+        }
+    }
 
-        while i < len {
-            let j = i + 1;
+    /// Create a new `Parser` which takes ownership of `args`.
+    ///
+    /// This behaves exactly like [`new`](#method.new), but avoids copying `args`, and allows the
+    /// remaining arguments to be taken back by value with
+    /// [`into_operands`](#method.into_operands) or [`into_parts`](#method.into_parts).
+    pub fn from_vec(args: Vec<String>, optstring: &str) -> Self {
+        let mut parser = Self::new(&[], optstring);
+        parser.args = args;
+        parser
+    }
 
-            if j < len && optstring[j] == ':' {
-                opts.insert(optstring[i], true);
-                i += 1;
-            } else {
-                opts.insert(optstring[i], false);
+    /// Create a new `Parser` from arguments as the operating system gives them, failing on the
+    /// first argument that is not valid Unicode.
+    ///
+    /// This behaves like [`new`](#method.new), but rather than requiring the caller to convert
+    /// `args` first, it returns an [`Error`](struct.Error.html) of kind
+    /// [`InvalidUnicode`](enum.ErrorKind.html#variant.InvalidUnicode), giving the
+    /// [`index`](struct.Error.html#method.index) of the offending argument, so that a file name
+    /// is never silently mangled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use getopt::Parser;
+    ///
+    /// let args: Vec<_> = std::env::args_os().collect();
+    /// let opts = match Parser::new_checked_os(&args, "ab:") {
+    ///     Ok(opts) => opts,
+    ///     Err(error) => {
+    ///         eprintln!("{}", error);
+    ///         std::process::exit(error.exit_code());
+    ///     },
+    /// };
+    /// # let _ = opts;
+    /// ```
+    pub fn new_checked_os(args: &[OsString], optstring: &str) -> Result<Self> {
+        let mut checked = Vec::with_capacity(args.len());
+        for (index, arg) in args.iter().enumerate() {
+            match arg.to_str() {
+                Some(arg) => checked.push(arg.to_string()),
+                None => {
+                    return Err(Error::invalid_unicode(&arg.to_string_lossy(), index));
+                },
             }
-            i += 1;
         }
+        Ok(Self::from_vec(checked, optstring))
+    }
 
-        Self {
-            opts,
-            // "explode" the args into a vector of character vectors, to allow indexing
-            args: args.iter().map(|e| e.chars().collect()).collect(),
-            index: 1,
-            point: 0,
-        }
+    /// Replace the options recognised by the parser with those specified in `optstring`.
+    ///
+    /// The current position of the parser is unaffected.
+    pub fn set_optstring(&mut self, optstring: &str) {
+        self.opts = parse_optstring(optstring);
     }
 
     /// Return the current `index` of the parser.
@@ -142,12 +266,142 @@ impl Parser {
         self.index
     }
 
+    /// Return the current position of the parser, as an `(index, point)` pair.
+    ///
+    /// `index` is as returned by [`index`](#method.index); `point` is the offset, in characters,
+    /// of the next option character to be examined within `args[index]`.
+    /// A `point` of `0` means that the parser is at the start of an element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-abc", "-d"];
+    /// # let args: Vec<String> = vec!["program", "-abc", "-d"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "abcd");
+    ///
+    /// assert_eq!((1, 0), opts.position());
+    /// opts.next();
+    /// assert_eq!((1, 2), opts.position());
+    /// opts.next();
+    /// opts.next();
+    /// assert_eq!((2, 0), opts.position());
+    /// ```
+    pub fn position(&self) -> (usize, usize) {
+        match self.args.get(self.index) {
+            Some(arg) if self.point > 0 => (self.index, arg[..self.point].chars().count()),
+            _ => (self.index, self.point),
+        }
+    }
+
+    /// Return the offset, in characters, of the next option character to be examined within
+    /// the current argument.
+    ///
+    /// This is the `point` of [`position`](#method.position).
+    pub fn point(&self) -> usize {
+        self.position().1
+    }
+
+    /// Return the argument the parser is currently at, `args[index]`, or `None` if there are no
+    /// arguments left.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-abc", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-abc", "foo"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "abc");
+    /// opts.next();
+    ///
+    /// assert_eq!((Some("-abc"), 2), (opts.current_arg(), opts.point()));
+    /// opts.next();
+    /// opts.next();
+    /// assert_eq!((Some("foo"), 0), (opts.current_arg(), opts.point()));
+    /// opts.set_index(3);
+    /// assert_eq!(None, opts.current_arg());
+    /// ```
+    pub fn current_arg(&self) -> Option<&str> {
+        self.args.get(self.index).map(String::as_str)
+    }
+
+    /// Return a readable, multi-line summary of the parser's state, for use in bug reports.
+    ///
+    /// The summary shows the options the parser recognises (in optstring form), its
+    /// [`position`](#method.position), the current argument with a caret under the next option
+    /// character to be examined, and how many times each option has been seen.
+    /// The exact format is not stable, and should not be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-abc", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-abc", "foo"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab:c");
+    /// opts.next();
+    ///
+    /// let report = opts.debug_report();
+    /// assert!(report.contains("optstring: ab:c"));
+    /// assert!(report.contains("-abc\n             ^"));
+    /// ```
+    pub fn debug_report(&self) -> String {
+        let mut opts: Vec<(&char, &bool)> = self.opts.iter().collect();
+        opts.sort();
+        let mut optstring = String::new();
+        for (&opt, &takes_arg) in opts {
+            optstring.push(opt);
+            if takes_arg {
+                optstring.push(':');
+            }
+        }
+
+        let (index, point) = self.position();
+        let mut report = format!("optstring: {}\n", optstring);
+        report.push_str(&format!(
+            "position:  index {}, point {} (of {} arguments)\n",
+            index,
+            point,
+            self.args.len()
+        ));
+        match self.args.get(index) {
+            None => report.push_str("current:   (none)\n"),
+            Some(arg) => {
+                report.push_str(&format!("current:   {}\n", arg));
+                report.push_str(&format!("           {}^\n", " ".repeat(point)));
+            },
+        }
+
+        let mut counts: Vec<(&char, &usize)> = self.counts.iter().collect();
+        counts.sort();
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|&(opt, count)| format!("{:?} x{}", opt, count))
+            .collect();
+        report.push_str(&format!("seen:      {}\n", counts.join(", ")));
+
+        report
+    }
+
     // `point` must be reset to 0 whenever `index` is changed
 
     /// Modify the current `index` of the parser.
     pub fn set_index(&mut self, value: usize) {
         self.index = value;
         self.point = 0;
+        self.stop = None;
     }
 
     /// Increment the current `index` of the parser.
@@ -157,159 +411,1361 @@ impl Parser {
         self.index += 1;
         self.point = 0;
     }
-}
 
-impl Iterator for Parser {
-    type Item = Result<Opt>;
+    /// Abandon the rest of the current group of options, moving on to the next element of
+    /// `args`.
+    ///
+    /// This does nothing if the parser is not in the middle of an element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-xyz", "-a"];
+    /// # let args: Vec<String> = vec!["program", "-xyz", "-a"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    ///
+    /// assert!(opts.next().unwrap().is_err());
+    /// opts.skip_cluster();
+    /// assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    /// ```
+    pub fn skip_cluster(&mut self) {
+        if self.point > 0 {
+            self.incr_index();
+        }
+    }
 
-    /// Returns the next option, if any.
+    /// Classify `arg` as the parser would interpret it at the start of an element, without
+    /// changing the state of the parser.
     ///
-    /// Returns an [`Error`](struct.Error.html) if an unexpected option is encountered or if an
-    /// expected argument is not found.
+    /// Whether an element is an option-argument depends on the element before it, so `arg` is
+    /// classified as if it were not one.
+    /// A lone `-` is an operand, unless [`set_dash_option`](#method.set_dash_option) makes it an
+    /// option.
     ///
-    /// Parsing stops at the first non-hyphenated argument; or at the first argument matching "-";
-    /// or after the first argument matching "--".
+    /// Options are looked up as [`next`](#method.next) would look them up, and an option which
+    /// takes an argument ends the cluster, taking the rest of it as its argument.
+    /// Checks on the argument itself, such as validators or rejecting option-like arguments, are
+    /// not applied.
     ///
-    /// When no more options are available, `next` returns `None`.
+    /// # Example
     ///
-    /// # Examples
+    /// ```
+    /// use getopt::{Class, Parser};
     ///
-    /// ## "-"
+    /// let opts = Parser::new(&[], "ab:");
+    ///
+    /// assert_eq!(Class::Options, opts.classify("-abfoo"));
+    /// assert_eq!(Class::NeedsArgument('b'), opts.classify("-ab"));
+    /// assert_eq!(Class::Options, opts.classify("-bax"));
+    /// assert_eq!(Class::Unknown('x'), opts.classify("-axb"));
+    /// assert_eq!(Class::Separator, opts.classify("--"));
+    /// assert_eq!(Class::Operand, opts.classify("-"));
     /// ```
-    /// use getopt::Parser;
+    pub fn classify(&self, arg: &str) -> Class {
+        if !arg.starts_with('-') {
+            return Class::Operand;
+        }
+
+        if arg == "-" {
+            return if self.dash_option {
+                Class::Options
+            } else {
+                Class::Operand
+            };
+        }
+
+        if arg == "--" {
+            return if self.delimiter {
+                Class::Separator
+            } else {
+                Class::Operand
+            };
+        }
+
+        let mut chars = arg[1..].chars();
+        while let Some(opt) = chars.next() {
+            match self.opts.get(&opt) {
+                None => return Class::Unknown(opt),
+                Some(false) if !self.grouping && !chars.as_str().is_empty() => {
+                    return Class::Unexpected(opt)
+                },
+                Some(false) => (),
+                Some(true) if chars.as_str().is_empty() => return Class::NeedsArgument(opt),
+                Some(true) => break,
+            }
+        }
+
+        Class::Options
+    }
+
+    /// Return whether the parser follows the given [`Guideline`](enum.Guideline.html).
+    pub fn guideline(&self, guideline: Guideline) -> bool {
+        match guideline {
+            Guideline::Grouping => self.grouping,
+            Guideline::Delimiter => self.delimiter,
+        }
+    }
+
+    /// Choose whether the parser follows the given [`Guideline`](enum.Guideline.html).
     ///
-    /// // args = ["program", "-", "-a"];
-    /// # let args: Vec<String> = vec!["program", "-", "-a"]
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Guideline, Opt, Parser};
+    ///
+    /// // args = ["program", "-ab", "-c"];
+    /// # let args: Vec<String> = vec!["program", "-ab", "-c"]
     /// #     .into_iter()
     /// #     .map(String::from)
     /// #     .collect();
-    /// let mut opts = Parser::new(&args, "a");
+    /// let mut opts = Parser::new(&args, "abc");
+    /// opts.set_guideline(Guideline::Grouping, false);
     ///
-    /// assert_eq!(None, opts.next());
-    /// assert_eq!("-", args[opts.index()]);
+    /// assert_eq!(
+    ///     "option does not take an argument -- 'a' in '-ab' (argument 1)",
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// assert_eq!(Some(Ok(Opt('c', None))), opts.next());
     /// ```
+    pub fn set_guideline(&mut self, guideline: Guideline, enabled: bool) {
+        match guideline {
+            Guideline::Grouping => self.grouping = enabled,
+            Guideline::Delimiter => self.delimiter = enabled,
+        }
+    }
+
+    /// Report a lone `-` as the option `'-'`, instead of stopping at it.
+    ///
+    /// By default, `-` is an operand like any other, and parsing stops there.
+    /// When enabled, it is returned as `Opt('-', None)` and parsing continues after it, so that
+    /// programs which take `-` to mean standard input can handle it in their option loop.
+    ///
+    /// # Example
     ///
-    /// ## "--"
     /// ```
-    /// use getopt::Parser;
+    /// use getopt::{Opt, Parser};
     ///
-    /// // args = ["program", "--", "-a"];
-    /// # let args: Vec<String> = vec!["program", "--", "-a"]
+    /// // args = ["program", "-a", "-", "-b"];
+    /// # let args: Vec<String> = vec!["program", "-a", "-", "-b"]
     /// #     .into_iter()
     /// #     .map(String::from)
     /// #     .collect();
-    /// let mut opts = Parser::new(&args, "a");
+    /// let mut opts = Parser::new(&args, "ab");
+    /// opts.set_dash_option(true);
     ///
+    /// assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    /// assert_eq!(Some(Ok(Opt('-', None))), opts.next());
+    /// assert_eq!(Some(Ok(Opt('b', None))), opts.next());
     /// assert_eq!(None, opts.next());
-    /// assert_eq!("-a", args[opts.index()]);
     /// ```
+    pub fn set_dash_option(&mut self, enabled: bool) {
+        self.dash_option = enabled;
+    }
+
+    /// Choose whether a separate option-argument that looks like an option is rejected.
+    ///
+    /// By default, as POSIX requires, the element following an option that takes an argument is
+    /// always taken as that argument, so `-o -x` gives `-o` the value `-x`.
+    /// That is almost always a mistake, so when enabled, such a value is reported as an
+    /// [`InvalidValue`](enum.ErrorKind.html#variant.InvalidValue) error and left in place to be
+    /// parsed next.
+    /// A lone `-` is still accepted, as are values attached to their option, like `-o-x`.
+    ///
+    /// # Example
     ///
-    /// ## Unexpected option:
     /// ```
-    /// use getopt::Parser;
+    /// use getopt::{Opt, Parser};
     ///
-    /// // args = ["program", "-b"];
-    /// # let args: Vec<String> = vec!["program", "-b"]
+    /// // args = ["program", "-o", "-x", "-o-y"];
+    /// # let args: Vec<String> = vec!["program", "-o", "-x", "-o-y"]
     /// #     .into_iter()
     /// #     .map(String::from)
     /// #     .collect();
-    /// let mut opts = Parser::new(&args, "a");
+    /// let mut opts = Parser::new(&args, "o:x");
+    /// opts.set_reject_option_like(true);
     ///
     /// assert_eq!(
-    ///     "unknown option -- 'b'".to_string(),
+    ///     "invalid value \"-x\" for option -- 'o' in '-o' (argument 1): argument looks like an option",
     ///     opts.next().unwrap().unwrap_err().to_string()
     /// );
+    /// assert_eq!(Some(Ok(Opt('x', None))), opts.next());
+    /// assert_eq!(Some(Ok(Opt('o', Some("-y".to_string())))), opts.next());
     /// ```
+    pub fn set_reject_option_like(&mut self, enabled: bool) {
+        self.reject_option_like = enabled;
+    }
+
+    /// Return why parsing stopped, or `None` if [`next`](#method.next) has not yet returned
+    /// `None`.
+    ///
+    /// # Example
     ///
-    /// ## Missing argument:
     /// ```
-    /// use getopt::Parser;
+    /// use getopt::{Parser, StopReason};
     ///
-    /// // args = ["program", "-a"];
-    /// # let args: Vec<String> = vec!["program", "-a"]
+    /// // args = ["program", "-a", "-", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-a", "-", "foo"]
     /// #     .into_iter()
     /// #     .map(String::from)
     /// #     .collect();
-    /// let mut opts = Parser::new(&args, "a:");
+    /// let mut opts = Parser::new(&args, "a");
+    ///
+    /// opts.next();
+    /// assert_eq!(None, opts.stop_reason());
+    /// assert_eq!(None, opts.next());
+    /// assert_eq!(Some(StopReason::Dash), opts.stop_reason());
+    /// ```
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop
+    }
+
+    /// Take the first operand as the name of a subcommand, in the manner of `git` or `cargo`.
+    ///
+    /// This is meant to be called once [`next`](#method.next) has returned `None`, so that the
+    /// global options have been parsed.
+    /// Returns the operand at [`index`](#method.index), if any, and moves the parser past it; the
+    /// subcommand's own options can then be parsed by calling
+    /// [`set_optstring`](#method.set_optstring) and continuing to iterate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-v", "add", "-f", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-v", "add", "-f", "foo"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "v");
+    ///
+    /// assert_eq!(Some(Ok(Opt('v', None))), opts.next());
+    /// assert_eq!(None, opts.next());
+    ///
+    /// let command = opts.subcommand();
+    /// assert_eq!(Some("add".to_string()), command);
+    ///
+    /// opts.set_optstring("f");
+    /// assert_eq!(Some(Ok(Opt('f', None))), opts.next());
+    /// assert_eq!(None, opts.next());
+    /// assert_eq!("foo", args[opts.index()]);
+    /// ```
+    pub fn subcommand(&mut self) -> Option<String> {
+        let command = self.args.get(self.index)?.clone();
+        self.incr_index();
+        Some(command)
+    }
+
+    /// Return how many times the option `opt` has been parsed so far.
+    ///
+    /// Every occurrence is counted, including those then rejected as errors by a limit, an
+    /// ordering constraint or a validator; unknown options and missing option-arguments are not
+    /// counted.
+    pub fn occurrences(&self, opt: char) -> usize {
+        self.counts.get(&opt).cloned().unwrap_or(0)
+    }
+
+    /// Limit how many times the option `opt` may be given.
+    ///
+    /// Every occurrence beyond the first `max` is reported as a
+    /// [`TooManyOccurrences`](enum.ErrorKind.html#variant.TooManyOccurrences) error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-o", "foo", "-obar"];
+    /// # let args: Vec<String> = vec!["program", "-o", "foo", "-obar"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "o:");
+    /// opts.set_max_occurrences('o', 1);
     ///
+    /// assert_eq!(Some(Ok(Opt('o', Some("foo".to_string())))), opts.next());
     /// assert_eq!(
-    ///     "option requires an argument -- 'a'".to_string(),
+    ///     "option given too many times -- 'o' in '-obar' (argument 3)",
     ///     opts.next().unwrap().unwrap_err().to_string()
     /// );
+    /// assert_eq!(2, opts.occurrences('o'));
     /// ```
-    fn next(&mut self) -> Option<Result<Opt>> {
-        if self.point == 0 {
-            /*
-             * Rationale excerpts below taken verbatim from "The Open Group Base Specifications
-             * Issue 7, 2018 edition", IEEE Std 1003.1-2017 (Revision of IEEE Std 1003.1-2008).
-             * Copyright © 2001-2018 IEEE and The Open Group.
-             */
-
-            /*
-             * If, when getopt() is called:
-             *      argv[optind]    is a null pointer
-             *      *argv[optind]   is not the character '-'
-             *      argv[optind]    points to the string "-"
-             * getopt() shall return -1 without changing optind.
-             */
-            if self.index >= self.args.len()
-                || self.args[self.index].is_empty()
-                || self.args[self.index][0] != '-'
-                || self.args[self.index].len() == 1
-            {
-                return None;
-            }
+    pub fn set_max_occurrences(&mut self, opt: char, max: usize) {
+        self.limits.insert(opt, max);
+    }
 
-            /*
-             * If:
-             *      argv[optind]    points to the string "--"
-             * getopt() shall return -1 after incrementing index.
-             */
-            if self.args[self.index][1] == '-' && self.args[self.index].len() == 2 {
-                self.incr_index();
-                return None;
-            }
+    /// Limit how many values may be collected for any one option that takes an argument.
+    ///
+    /// Every value beyond the first `max` for an option is reported as a
+    /// [`LimitExceeded`](enum.ErrorKind.html#variant.LimitExceeded) error, whose culprit is the
+    /// option, and parsing carries on.
+    /// Options without an argument may be given any number of times; use
+    /// [`set_max_occurrences`](#method.set_max_occurrences) to limit those, which is checked as
+    /// well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{ErrorKind, Opt, Parser};
+    ///
+    /// // args = ["program", "-vv", "-o", "a", "-vob"];
+    /// # let args: Vec<String> = vec!["program", "-vv", "-o", "a", "-vob"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "o:v");
+    /// opts.set_max_values(1);
+    ///
+    /// assert_eq!(4, opts.by_ref().take(4).filter(Result::is_ok).count());
+    /// let error = opts.next().unwrap().unwrap_err();
+    /// assert_eq!(ErrorKind::LimitExceeded, error.kind());
+    /// assert_eq!(
+    ///     "input limit exceeded: more than 1 values for option -- 'o' in '-vob' (argument 4)",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn set_max_values(&mut self, max: usize) {
+        self.max_values = Some(max);
+    }
 
-            // move past the starting '-'
-            self.point += 1;
-        }
+    /// Limit how many elements `args` may have, counting the program name.
+    ///
+    /// Input limits guard programs that parse untrusted command lines.
+    /// They are checked before the first option is parsed; if `args` is over a limit, the parser
+    /// returns a single [`LimitExceeded`](enum.ErrorKind.html#variant.LimitExceeded) error, whose
+    /// culprit is `-`, and then no more options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{ErrorKind, Parser};
+    ///
+    /// // args = ["program", "-a", "-b", "-c"];
+    /// # let args: Vec<String> = vec!["program", "-a", "-b", "-c"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "abc");
+    /// opts.set_max_args(3);
+    ///
+    /// let error = opts.next().unwrap().unwrap_err();
+    /// assert_eq!(ErrorKind::LimitExceeded, error.kind());
+    /// assert_eq!("input limit exceeded: more than 3 arguments in '-c' (argument 3)", error.to_string());
+    /// assert_eq!(Some(3), error.index());
+    /// assert_eq!(None, opts.next());
+    /// ```
+    pub fn set_max_args(&mut self, max: usize) {
+        self.max_args = Some(max);
+        self.input_checked = false;
+    }
 
-        let opt = self.args[self.index][self.point];
-        self.point += 1;
+    /// Limit the length in bytes of every element of `args`.
+    ///
+    /// See [`set_max_args`](#method.set_max_args) for how input limits are checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-o", "a-very-long-argument"];
+    /// # let args: Vec<String> = vec!["program", "-o", "a-very-long-argument"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "o:");
+    /// opts.set_max_arg_len(16);
+    ///
+    /// assert_eq!(
+    ///     "input limit exceeded: argument longer than 16 bytes in 'a-very-long-argument' (argument 2)",
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn set_max_arg_len(&mut self, max: usize) {
+        self.max_arg_len = Some(max);
+        self.input_checked = false;
+    }
 
-        match self.opts.get(&opt) {
-            None => {
-                if self.point >= self.args[self.index].len() {
-                    self.incr_index();
-                }
-                Some(Err(Error::new(ErrorKind::UnknownOption, opt)))
-            },
-            Some(false) => {
-                if self.point >= self.args[self.index].len() {
-                    self.incr_index();
-                }
+    /// Check every argument given to the option `opt` with `validator`.
+    ///
+    /// A value that `validator` rejects is reported as an
+    /// [`InvalidValue`](enum.ErrorKind.html#variant.InvalidValue) error, with the returned message
+    /// as its source.
+    /// The validator may be a closure that captures its surroundings, such as a range read from a
+    /// configuration file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{ErrorKind, Opt, Parser};
+    ///
+    /// // args = ["program", "-n", "42", "-n", "x"];
+    /// # let args: Vec<String> = vec!["program", "-n", "42", "-n", "x"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "n:");
+    /// let max = 100;
+    /// opts.set_validator('n', move |value| match value.parse::<u32>() {
+    ///     Ok(n) if n <= max => Ok(()),
+    ///     Ok(_) => Err(format!("more than {}", max)),
+    ///     Err(_) => Err("not a number".to_string()),
+    /// });
+    ///
+    /// assert_eq!(Some(Ok(Opt('n', Some("42".to_string())))), opts.next());
+    /// let error = opts.next().unwrap().unwrap_err();
+    /// assert_eq!(ErrorKind::InvalidValue, error.kind());
+    /// assert_eq!("invalid value \"x\" for option -- 'n' in '-n' (argument 3): not a number", error.to_string());
+    /// ```
+    pub fn set_validator<F>(&mut self, opt: char, validator: F)
+    where
+        F: Fn(&str) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        self.validators.insert(opt, Validator(Box::new(validator)));
+    }
 
-                Some(Ok(Opt(opt, None)))
+    /// Require every occurrence of the option `first` to come before any occurrence of the option
+    /// `then`.
+    ///
+    /// An occurrence of `first` following `then` is reported as an
+    /// [`OutOfOrder`](enum.ErrorKind.html#variant.OutOfOrder) error; [`position`](#method.position)
+    /// can be used to locate it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{ErrorKind, Opt, Parser};
+    ///
+    /// // args = ["program", "-c", "-e", "-c"];
+    /// # let args: Vec<String> = vec!["program", "-c", "-e", "-c"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ce");
+    /// opts.require_before('c', 'e');
+    ///
+    /// assert_eq!(Some(Ok(Opt('c', None))), opts.next());
+    /// assert_eq!(Some(Ok(Opt('e', None))), opts.next());
+    /// assert_eq!(ErrorKind::OutOfOrder, opts.next().unwrap().unwrap_err().kind());
+    /// ```
+    pub fn require_before(&mut self, first: char, then: char) {
+        self.before.push((first, then));
+    }
+
+    /// Require the option `opt`, if given, to be the first option.
+    ///
+    /// An occurrence of `opt` following any other option is reported as an
+    /// [`OutOfOrder`](enum.ErrorKind.html#variant.OutOfOrder) error.
+    pub fn require_first(&mut self, opt: char) {
+        self.first.push(opt);
+    }
+
+    /// Mark the option `opt` as deprecated, optionally naming the option that replaces it.
+    ///
+    /// Deprecated options are still parsed normally, but each occurrence records a
+    /// [`Warning`](struct.Warning.html), which can be retrieved with
+    /// [`warnings`](#method.warnings).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-o"];
+    /// # let args: Vec<String> = vec!["program", "-o"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "no");
+    /// opts.set_deprecated('o', Some('n'));
+    ///
+    /// assert_eq!(Some(Ok(Opt('o', None))), opts.next());
+    /// assert_eq!(
+    ///     "deprecated option -- 'o' (use 'n' instead)",
+    ///     opts.warnings()[0].to_string()
+    /// );
+    /// ```
+    pub fn set_deprecated(&mut self, opt: char, replacement: Option<char>) {
+        self.deprecated.insert(opt, replacement);
+    }
+
+    /// Return the warnings recorded so far, in the order they were encountered.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Choose whether unknown options are passed through rather than reported as errors.
+    ///
+    /// In pass-through mode, an unknown option is not returned by [`next`](#method.next);
+    /// instead, it is collected along with the rest of its element, which is assumed to be its
+    /// attached argument, and parsing continues with the next element.
+    /// The collected options can be retrieved with [`passed_through`](#method.passed_through),
+    /// e.g. to be forwarded to another program.
+    ///
+    /// An unknown option cannot be known to take an argument in the following element, so such
+    /// arguments are treated like any other element: usually as the first operand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-axv", "-p22", "-b", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-axv", "-p22", "-b", "foo"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab");
+    /// opts.set_pass_through(true);
+    ///
+    /// let found: Vec<Opt> = opts.ok_only().collect();
+    ///
+    /// assert_eq!(vec![Opt('a', None), Opt('b', None)], found);
+    /// assert_eq!(&["-xv".to_string(), "-p22".to_string()], opts.passed_through());
+    /// assert_eq!("foo", args[opts.index()]);
+    /// ```
+    pub fn set_pass_through(&mut self, enabled: bool) {
+        self.pass_through = enabled;
+    }
+
+    /// Return the unknown options collected so far in pass-through mode, in the order they were
+    /// encountered.
+    pub fn passed_through(&self) -> &[String] {
+        &self.passed
+    }
+
+    /// Parse all remaining options, returning the recognised ones along with every unrecognised
+    /// argument, in the manner of Python's `argparse.parse_known_args`.
+    ///
+    /// The unrecognised arguments are the unknown options, as collected in
+    /// [pass-through mode](#method.set_pass_through), followed by the operands.
+    /// Missing option-arguments are still reported as errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-a", "-x", "-b", "foo", "bar"];
+    /// # let args: Vec<String> = vec!["program", "-a", "-x", "-b", "foo", "bar"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab:");
+    /// let (known, unknown) = opts.parse_known()?;
+    ///
+    /// assert_eq!(vec![Opt('a', None), Opt('b', Some("foo".to_string()))], known);
+    /// assert_eq!(vec!["-x", "bar"], unknown);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_known(&mut self) -> Result<(Vec<Opt>, Vec<String>)> {
+        let pass_through = self.pass_through;
+        self.pass_through = true;
+        let known: Result<Vec<Opt>> = self.by_ref().collect();
+        self.pass_through = pass_through;
+
+        let mut unknown = self.passed.clone();
+        unknown.extend(self.args.iter().skip(self.index).cloned());
+
+        Ok((known?, unknown))
+    }
+
+    /// Parse all remaining options, collecting them into a map keyed by option character.
+    ///
+    /// Each option present on the command line maps to the list of its arguments, in the order
+    /// given; options that take no argument contribute a `None` for each occurrence.
+    ///
+    /// Parsing stops at the first error, which is returned.
+    /// On success, [`index`](#method.index) points to the first non-option argument, just as it
+    /// would after iterating manually.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // args = ["program", "-a", "-b", "foo", "-ab", "bar", "baz"];
+    /// # let args: Vec<String> = vec!["program", "-a", "-b", "foo", "-ab", "bar", "baz"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = getopt::Parser::new(&args, "ab:c");
+    /// let map = opts.collect_map()?;
+    ///
+    /// assert_eq!(vec![None, None], map[&'a']);
+    /// assert_eq!(vec![Some("foo".to_string()), Some("bar".to_string())], map[&'b']);
+    /// assert!(!map.contains_key(&'c'));
+    /// assert_eq!("baz", args[opts.index()]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collect_map(&mut self) -> Result<HashMap<char, Vec<Option<String>>>> {
+        let mut map = HashMap::new();
+
+        for opt in self {
+            let Opt(opt, arg) = opt?;
+            map.entry(opt).or_insert_with(Vec::new).push(arg);
+        }
+
+        Ok(map)
+    }
+
+    /// Consume the parser, returning the arguments from [`index`](#method.index) onwards.
+    ///
+    /// Once all options have been parsed, these are the operands.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// let args: Vec<String> = vec!["program", "-a", "foo", "bar"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let mut opts = Parser::from_vec(args, "a");
+    ///
+    /// assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    /// assert_eq!(None, opts.next());
+    /// assert_eq!(vec!["foo", "bar"], opts.into_operands());
+    /// ```
+    pub fn into_operands(mut self) -> Vec<String> {
+        let index = self.index.min(self.args.len());
+        self.args.split_off(index)
+    }
+
+    /// Consume the parser, returning the arguments it has not yet parsed.
+    ///
+    /// This is like [`into_operands`](#method.into_operands), except that a cluster the parser
+    /// has stopped part of the way through is kept, with the options already parsed removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// let args: Vec<String> = vec!["program", "-abc", "foo"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let mut opts = Parser::from_vec(args, "abc");
+    ///
+    /// assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    /// assert_eq!(vec!["-bc", "foo"], opts.into_remaining());
+    /// ```
+    pub fn into_remaining(mut self) -> Vec<String> {
+        let index = self.index.min(self.args.len());
+        let mut remaining = self.args.split_off(index);
+        if self.point > 0 {
+            if let Some(arg) = remaining.first_mut() {
+                arg.drain(1..self.point);
+            }
+        }
+        remaining
+    }
+
+    /// Consume the parser, parsing all remaining options and returning them along with the
+    /// operands.
+    ///
+    /// Parsing stops at the first error, which is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use getopt::{Opt, Parser};
+    ///
+    /// let args: Vec<String> = vec!["program", "-a", "-b", "foo", "bar"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let (opts, operands) = Parser::from_vec(args, "ab:").into_parts()?;
+    ///
+    /// assert_eq!(vec![Opt('a', None), Opt('b', Some("foo".to_string()))], opts);
+    /// assert_eq!(vec!["bar"], operands);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_parts(mut self) -> Result<(Vec<Opt>, Vec<String>)> {
+        let opts = self.by_ref().collect::<Result<Vec<Opt>>>()?;
+        Ok((opts, self.into_operands()))
+    }
+
+    /// Consume the parser like [`into_parts`](#method.into_parts), but on error print the
+    /// diagnostic to standard error and exit the process.
+    ///
+    /// The diagnostic includes the program name and usage synopsis, if they have been set with
+    /// [`set_program_name`](#method.set_program_name) and [`set_usage`](#method.set_usage).
+    /// The exit status is that of [`Error::exit_code`](struct.Error.html#method.exit_code).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// let args: Vec<String> = vec!["program", "-a", "foo"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let mut opts = Parser::from_vec(args, "ab");
+    /// opts.set_program_name("program");
+    /// opts.set_usage("usage: program [-ab] file");
+    /// let (opts, operands) = opts.parse_or_exit();
+    ///
+    /// assert_eq!(vec![Opt('a', None)], opts);
+    /// assert_eq!(vec!["foo"], operands);
+    /// ```
+    pub fn parse_or_exit(self) -> (Vec<Opt>, Vec<String>) {
+        match self.into_parts() {
+            Ok(parts) => parts,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(error.exit_code());
             },
-            Some(true) => {
-                let arg: String = if self.point >= self.args[self.index].len() {
+        }
+    }
+
+    /// Return an iterator over the remaining options which silently skips any errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-axb", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-axb", "foo"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab");
+    /// let found: Vec<Opt> = opts.ok_only().collect();
+    ///
+    /// assert_eq!(vec![Opt('a', None), Opt('b', None)], found);
+    /// assert_eq!("foo", args[opts.index()]);
+    /// ```
+    pub fn ok_only(&mut self) -> OkOnly<'_> {
+        OkOnly { parser: self }
+    }
+
+    /// Return an iterator over the remaining options which ends after yielding the first error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-axb", "foo"];
+    /// # let args: Vec<String> = vec!["program", "-axb", "foo"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab");
+    /// let mut strict = opts.strict();
+    ///
+    /// assert_eq!(Some(Ok(Opt('a', None))), strict.next());
+    /// assert!(strict.next().unwrap().is_err());
+    /// assert_eq!(None, strict.next());
+    /// ```
+    pub fn strict(&mut self) -> Strict<'_> {
+        Strict {
+            parser: self,
+            done: false,
+        }
+    }
+
+    /// Set the name of the program to attach to every error the parser returns from now on.
+    ///
+    /// Errors then display in the conventional format, `name: unknown option -- 'x'`.
+    /// To attach a name to only some errors, use [`with_context`](#method.with_context).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-x"];
+    /// # let args: Vec<String> = vec!["program", "-x"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    /// opts.set_program_name("program");
+    ///
+    /// assert_eq!(
+    ///     "program: unknown option -- 'x' in '-x' (argument 1)",
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn set_program_name(&mut self, name: &str) {
+        self.program_name = Some(name.to_string());
+    }
+
+    /// Set a usage synopsis to show with every error the parser returns from now on.
+    ///
+    /// See [`Error::set_usage`](struct.Error.html#method.set_usage).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-x"];
+    /// # let args: Vec<String> = vec!["program", "-x"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab");
+    /// opts.set_program_name("program");
+    /// opts.set_usage("usage: program [-ab] file ...");
+    ///
+    /// assert_eq!(
+    ///     "program: unknown option -- 'x' in '-x' (argument 1)\nusage: program [-ab] file ...",
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn set_usage(&mut self, usage: &str) {
+        self.usage = Some(usage.to_string());
+    }
+
+    /// Set the [`Dialect`](enum.Dialect.html) of every error the parser returns from now on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Dialect, Parser};
+    ///
+    /// // args = ["program", "-x"];
+    /// # let args: Vec<String> = vec!["program", "-x"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab");
+    /// opts.set_program_name("program");
+    /// opts.set_dialect(Dialect::Glibc);
+    ///
+    /// assert_eq!(
+    ///     "program: invalid option -- 'x'",
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    /// Name the option that shows the program's help, so that every error the parser returns
+    /// from now on ends with a hint to use it, in the GNU style.
+    ///
+    /// The hint names the program as set with [`set_program_name`](#method.set_program_name),
+    /// or else by the file stem of the first element of `args`, as
+    /// [`cli::name`](cli/fn.name.html) does; with no name to give, there is no hint.
+    /// See [`Error::set_hint`](struct.Error.html#method.set_hint).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-x"];
+    /// # let args: Vec<String> = vec!["program", "-x"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "abh");
+    /// opts.set_program_name("program");
+    /// opts.set_help_option('h');
+    ///
+    /// assert_eq!(
+    ///     "program: unknown option -- 'x' in '-x' (argument 1)\nTry 'program -h' for more information.",
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn set_help_option(&mut self, opt: char) {
+        self.help_option = Some(opt);
+    }
+
+    /// Return an iterator over the remaining options which attaches the program name `name` to
+    /// every error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-x"];
+    /// # let args: Vec<String> = vec!["program", "-x"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    ///
+    /// assert_eq!(
+    ///     "program: unknown option -- 'x' in '-x' (argument 1)",
+    ///     opts.with_context("program").next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn with_context(&mut self, name: &str) -> WithContext<'_> {
+        WithContext {
+            parser: self,
+            name: name.to_string(),
+        }
+    }
+
+    /// Return an iterator over the remaining options which prints every error to `sink`, in the
+    /// conventional format `name: unknown option -- 'x'`, before returning it.
+    ///
+    /// This mirrors the behaviour of C `getopt` when `opterr` is set, with `sink` usually being
+    /// [`std::io::stderr`](https://doc.rust-lang.org/std/io/fn.stderr.html).
+    /// Errors writing to `sink` are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-xa"];
+    /// # let args: Vec<String> = vec!["program", "-xa"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    /// let mut output = Vec::new();
+    /// let found: Vec<_> = opts.report_errors("program", &mut output).collect();
+    ///
+    /// assert!(found[0].is_err());
+    /// assert_eq!(Ok(Opt('a', None)), found[1]);
+    /// assert_eq!(b"program: unknown option -- 'x' in '-xa' (argument 1)\n", &output[..]);
+    /// ```
+    pub fn report_errors<W: Write>(&mut self, name: &str, sink: W) -> Report<'_, W> {
+        Report {
+            parser: self,
+            name: name.to_string(),
+            sink,
+        }
+    }
+
+    /// Return an iterator over the remaining options which passes every error to `on_error`
+    /// and carries on.
+    ///
+    /// Unlike [`ok_only`](#method.ok_only), errors are not lost; unlike the parser itself, the
+    /// iterator yields plain [`Opt`](struct.Opt.html)s.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-xa"];
+    /// # let args: Vec<String> = vec!["program", "-xa"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    /// let mut errors = Vec::new();
+    /// let found: Vec<_> = opts.lenient(|e| errors.push(e.to_string())).collect();
+    ///
+    /// assert_eq!(vec![Opt('a', None)], found);
+    /// assert_eq!(vec!["unknown option -- 'x' in '-xa' (argument 1)"], errors);
+    /// ```
+    pub fn lenient<F: FnMut(Error)>(&mut self, on_error: F) -> Lenient<'_, F> {
+        Lenient {
+            parser: self,
+            on_error,
+        }
+    }
+
+    /// Return an iterator over the remaining options which also gives where each option was
+    /// found, as a [`Located`](struct.Located.html).
+    ///
+    /// This suits programs that rewrite command lines, such as dropping one occurrence of an
+    /// option.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use getopt::{Opt, Parser};
+    ///
+    /// // args = ["program", "-ab", "foo", "-bbar"];
+    /// # let args: Vec<String> = vec!["program", "-ab", "foo", "-bbar"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "ab:");
+    /// let found = opts.with_positions().collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(Opt('a', None), *found[0].opt());
+    /// assert_eq!((1, 1, None), (found[0].index(), found[0].offset(), found[0].value_index()));
+    /// assert_eq!((1, 2, Some(2)), (found[1].index(), found[1].offset(), found[1].value_index()));
+    /// assert_eq!((3, 1, Some(3)), (found[2].index(), found[2].offset(), found[2].value_index()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_positions(&mut self) -> WithPositions<'_> {
+        WithPositions { parser: self }
+    }
+
+    // Attach the position of the option just parsed.
+    pub(crate) fn locate(&self, opt: Opt) -> Located {
+        let arg = &self.args[self.last];
+        let offset = arg[..self.last_point].chars().count();
+        let value_index = match opt.1 {
+            None => None,
+            Some(_) if self.last_point + opt.0.len_utf8() < arg.len() => Some(self.last),
+            Some(_) => Some(self.last + 1),
+        };
+        Located::new(opt, self.last, offset, value_index)
+    }
+
+    // Parse the next option, then enforce the constraints set up on the parser.
+    fn check_next(&mut self) -> Option<Result<Opt>> {
+        if self.over_limit {
+            return None;
+        }
+        if !self.input_checked {
+            self.input_checked = true;
+            if let Some(error) = self.check_input() {
+                self.over_limit = true;
+                return Some(Err(error));
+            }
+        }
+
+        let next = self.parse_next();
+
+        if let Some(Ok(Opt(opt, _))) = next {
+            let out_of_order = self
+                .before
+                .iter()
+                .any(|&(first, then)| first == opt && self.occurrences(then) > 0)
+                || (self.first.contains(&opt) && self.counts.keys().any(|&o| o != opt));
+
+            let count = self.counts.entry(opt).or_insert(0);
+            *count += 1;
+
+            match self.limits.get(&opt) {
+                Some(&max) if *count > max => {
+                    return Some(Err(Error::new(ErrorKind::TooManyOccurrences, opt)));
+                },
+                // a repeated flag usually means something (e.g. more verbosity); a repeated
+                // option-argument usually replaces the earlier one
+                None if *count > 1 && self.opts.get(&opt) == Some(&true) => {
+                    self.warnings
+                        .push(Warning::new(WarningKind::Duplicate, opt));
+                },
+                _ => (),
+            }
+            // only an option that takes an argument collects a value each time
+            match self.max_values {
+                Some(max) if *count > max && self.opts.get(&opt) == Some(&true) => {
+                    return Some(Err(Error::limit_exceeded(
+                        opt,
+                        format!("more than {} values for option -- {:?}", max, opt),
+                    )));
+                },
+                _ => (),
+            }
+
+            if out_of_order {
+                return Some(Err(Error::new(ErrorKind::OutOfOrder, opt)));
+            }
+        }
+
+        if let Some(Ok(Opt(opt, Some(ref arg)))) = next {
+            if let Some(Validator(validator)) = self.validators.get(&opt) {
+                if let Err(message) = validator(arg) {
+                    return Some(Err(Error::invalid_value(opt, arg, message)));
+                }
+            }
+        }
+
+        next
+    }
+
+    // Check `args` against the input limits, pointing `last` at the offending element.
+    fn check_input(&mut self) -> Option<Error> {
+        if let Some(max) = self.max_args {
+            if self.args.len() > max {
+                self.last = max;
+                return Some(Error::limit_exceeded(
+                    '-',
+                    format!("more than {} arguments", max),
+                ));
+            }
+        }
+        if let Some(max) = self.max_arg_len {
+            if let Some(index) = self.args.iter().position(|arg| arg.len() > max) {
+                self.last = index;
+                return Some(Error::limit_exceeded(
+                    '-',
+                    format!("argument longer than {} bytes", max),
+                ));
+            }
+        }
+        None
+    }
+
+    // The option parsing proper; `check_next` applies the checks that depend on what was parsed.
+    fn parse_next(&mut self) -> Option<Result<Opt>> {
+        self.stop = None;
+
+        // unknown options passed through are skipped in a loop, as there may be any number
+        loop {
+            if self.point == 0 {
+                /*
+                 * Rationale excerpts below taken verbatim from "The Open Group Base Specifications
+                 * Issue 7, 2018 edition", IEEE Std 1003.1-2017 (Revision of IEEE Std 1003.1-2008).
+                 * Copyright © 2001-2018 IEEE and The Open Group.
+                 */
+
+                /*
+                 * If, when getopt() is called:
+                 *      argv[optind]    is a null pointer
+                 *      *argv[optind]   is not the character '-'
+                 *      argv[optind]    points to the string "-"
+                 * getopt() shall return -1 without changing optind.
+                 */
+                if self.index >= self.args.len() {
+                    self.stop = Some(StopReason::End);
+                    return None;
+                }
+                if !self.args[self.index].starts_with('-') {
+                    self.stop = Some(StopReason::Operand);
+                    return None;
+                }
+                if self.args[self.index] == "-" {
+                    if self.dash_option {
+                        self.last = self.index;
+                        self.last_point = 0;
+                        self.incr_index();
+                        return Some(Ok(Opt('-', None)));
+                    }
+                    self.stop = Some(StopReason::Dash);
+                    return None;
+                }
+
+                /*
+                 * If:
+                 *      argv[optind]    points to the string "--"
+                 * getopt() shall return -1 after incrementing index.
+                 */
+                if self.args[self.index] == "--" {
+                    self.stop = Some(StopReason::Separator);
+                    if self.delimiter {
+                        self.incr_index();
+                    }
+                    return None;
+                }
+
+                if self.args[self.index].starts_with("--") {
+                    self.warnings
+                        .push(Warning::new(WarningKind::LongOptionLike, '-'));
+                }
+
+                // move past the starting '-'
+                self.point += 1;
+            }
+
+            // `point` is a byte offset, always on a character boundary
+            let opt = match self.args[self.index][self.point..].chars().next() {
+                Some(opt) => opt,
+                None => unreachable!(),
+            };
+            let start = self.point;
+            self.point += opt.len_utf8();
+            self.last = self.index;
+            self.last_point = start;
+
+            if self.opts.contains_key(&opt) {
+                if let Some(&replacement) = self.deprecated.get(&opt) {
+                    self.warnings.push(Warning::deprecated(opt, replacement));
+                }
+            }
+
+            break match self.opts.get(&opt) {
+                None if self.pass_through => {
+                    let passed = format!("-{}", &self.args[self.index][start..]);
+                    self.passed.push(passed);
+                    self.incr_index();
+                    continue;
+                },
+                None => {
+                    if self.point >= self.args[self.index].len() || !self.grouping {
+                        self.incr_index();
+                    }
+                    Some(Err(Error::new(ErrorKind::UnknownOption, opt)))
+                },
+                Some(false) => {
+                    if self.point >= self.args[self.index].len() {
+                        self.incr_index();
+                    } else if !self.grouping {
+                        self.incr_index();
+                        return Some(Err(Error::new(ErrorKind::UnexpectedArgument, opt)));
+                    }
+
+                    Some(Ok(Opt(opt, None)))
+                },
+                Some(true) => {
+                    let arg = if self.point >= self.args[self.index].len() {
+                        self.incr_index();
+                        if self.index >= self.args.len() {
+                            return Some(Err(Error::new(ErrorKind::MissingArgument, opt)));
+                        }
+                        // the suspect element is left in place, to be parsed as the option it
+                        // most likely is
+                        let arg = &self.args[self.index];
+                        if self.reject_option_like && arg.starts_with('-') && arg != "-" {
+                            return Some(Err(Error::invalid_value(
+                                opt,
+                                arg,
+                                "argument looks like an option",
+                            )));
+                        }
+                        arg.clone()
+                    } else {
+                        self.args[self.index][self.point..].to_string()
+                    };
+
                     self.incr_index();
-                    if self.index >= self.args.len() {
-                        return Some(Err(Error::new(ErrorKind::MissingArgument, opt)));
+
+                    Some(Ok(Opt(opt, Some(arg))))
+                },
+            };
+        }
+    }
+}
+
+/// Map each option character in `optstring` to whether it takes an argument.
+pub(crate) fn parse_optstring(optstring: &str) -> HashMap<char, bool> {
+    let optstring: Vec<char> = optstring.chars().collect();
+    let mut opts = HashMap::new();
+    let mut i = 0;
+    let len = optstring.len();
+
+    while i < len {
+        let j = i + 1;
+
+        if j < len && optstring[j] == ':' {
+            opts.insert(optstring[i], true);
+            i += 1;
+        } else {
+            opts.insert(optstring[i], false);
+        }
+        i += 1;
+    }
+
+    opts
+}
+
+impl Iterator for Parser {
+    type Item = Result<Opt>;
+
+    /// Returns the next option, if any.
+    ///
+    /// Returns an [`Error`](struct.Error.html) if an unexpected option is encountered or if an
+    /// expected argument is not found.
+    ///
+    /// Parsing stops at the first non-hyphenated argument; or at the first argument matching "-";
+    /// or after the first argument matching "--".
+    ///
+    /// When no more options are available, `next` returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ## "-"
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-", "-a"];
+    /// # let args: Vec<String> = vec!["program", "-", "-a"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    ///
+    /// assert_eq!(None, opts.next());
+    /// assert_eq!("-", args[opts.index()]);
+    /// ```
+    ///
+    /// ## "--"
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "--", "-a"];
+    /// # let args: Vec<String> = vec!["program", "--", "-a"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    ///
+    /// assert_eq!(None, opts.next());
+    /// assert_eq!("-a", args[opts.index()]);
+    /// ```
+    ///
+    /// ## Unexpected option:
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-b"];
+    /// # let args: Vec<String> = vec!["program", "-b"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a");
+    ///
+    /// assert_eq!(
+    ///     "unknown option -- 'b' in '-b' (argument 1)".to_string(),
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    ///
+    /// ## Missing argument:
+    /// ```
+    /// use getopt::Parser;
+    ///
+    /// // args = ["program", "-a"];
+    /// # let args: Vec<String> = vec!["program", "-a"]
+    /// #     .into_iter()
+    /// #     .map(String::from)
+    /// #     .collect();
+    /// let mut opts = Parser::new(&args, "a:");
+    ///
+    /// assert_eq!(
+    ///     "option requires an argument -- 'a' in '-a' (argument 1)".to_string(),
+    ///     opts.next().unwrap().unwrap_err().to_string()
+    /// );
+    /// ```
+    fn next(&mut self) -> Option<Result<Opt>> {
+        match self.check_next() {
+            Some(Err(mut error)) => {
+                error.set_token(&self.args[self.last], self.last);
+                error.set_dialect(self.dialect);
+                if let Some(ref name) = self.program_name {
+                    error.set_program_name(name);
+                }
+                if let Some(ref usage) = self.usage {
+                    error.set_usage(usage);
+                }
+                if let Some(opt) = self.help_option {
+                    let name = match self.program_name {
+                        Some(ref name) => Some(name.clone()),
+                        None => self
+                            .args
+                            .first()
+                            .and_then(|arg| Path::new(arg).file_stem())
+                            .map(|stem| stem.to_string_lossy().into_owned()),
+                    };
+                    if let Some(name) = name {
+                        error.set_hint(&format!("Try '{} -{}' for more information.", name, opt));
                     }
-                    self.args[self.index].iter().collect()
-                } else {
-                    self.args[self.index]
-                        .clone()
-                        .split_off(self.point)
-                        .iter()
-                        .collect()
-                };
-
-                self.incr_index();
-
-                Some(Ok(Opt(opt, Some(arg))))
+                }
+                Some(Err(error))
             },
+            next => next,
         }
     }
 }
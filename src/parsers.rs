@@ -0,0 +1,329 @@
+//! Ready-made value types for common, human-friendly option-arguments.
+//!
+//! Each type implements [`FromStr`](https://doc.rust-lang.org/std/str/trait.FromStr.html), and
+//! so [`FromOptValue`](../trait.FromOptValue.html); the parsed value is in the public field.
+//!
+//! # Example
+//!
+//! ```
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use getopt::{parsers::ByteSize, Opt};
+//!
+//! let opt = Opt('s', Some("64K".to_string()));
+//! assert_eq!(Some(ByteSize(65536)), opt.parse_value()?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::BTreeMap, error, fmt, str::FromStr, time};
+
+use crate::opt::Opt;
+
+/// The error returned when a value cannot be parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    message: &'static str,
+}
+
+impl ParseError {
+    fn new(message: &'static str) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl error::Error for ParseError {}
+
+// Split a leading decimal number from the rest of the string.
+fn split_number(string: &str) -> Option<(f64, &str)> {
+    let end = string
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(string.len());
+    match string[..end].parse::<f64>() {
+        Ok(number) => Some((number, &string[end..])),
+        Err(_) => None,
+    }
+}
+
+/// A span of time, such as `30s`, `5m` or `1h30m`.
+///
+/// A duration is one or more numbers, each followed by a unit: `ms`, `s`, `m`, `h` or `d`.
+/// A single number without a unit is taken as seconds.
+/// Numbers may have a fractional part, as in `1.5h`.
+///
+/// # Example
+///
+/// ```
+/// use std::time;
+///
+/// use getopt::parsers::Duration;
+///
+/// assert_eq!(Ok(Duration(time::Duration::from_secs(90))), "1m30s".parse());
+/// assert_eq!(Ok(Duration(time::Duration::from_millis(250))), "250ms".parse());
+/// assert_eq!(Ok(Duration(time::Duration::from_secs(10))), "10".parse());
+/// assert!("5 minutes".parse::<Duration>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Duration(pub time::Duration);
+
+impl FromStr for Duration {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = ParseError::new("expected a duration, like 30s, 5m or 1h30m");
+        if string.is_empty() {
+            return Err(error);
+        }
+
+        let mut secs = 0.0;
+        let mut rest = string;
+        while !rest.is_empty() {
+            let (number, tail) = split_number(rest).ok_or_else(|| error.clone())?;
+            let end = tail
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .unwrap_or(tail.len());
+            let scale = match &tail[..end] {
+                "" if rest.len() == string.len() => 1.0,
+                "ms" => 0.001,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 60.0 * 60.0,
+                "d" => 24.0 * 60.0 * 60.0,
+                _ => return Err(error),
+            };
+            secs += number * scale;
+            rest = &tail[end..];
+        }
+
+        if !secs.is_finite() || secs >= u64::max_value() as f64 {
+            return Err(ParseError::new("duration is too long"));
+        }
+        Ok(Duration(time::Duration::from_secs_f64(secs)))
+    }
+}
+
+/// A number of bytes, such as `512`, `64K` or `2GiB`.
+///
+/// The suffixes `K`, `M`, `G`, `T`, `P` and `E` are powers of 1024, whether they are written
+/// alone (`K`) or in IEC form (`KiB`); written with a plain `B` (`KB`), they are powers of 1000
+/// instead, in the manner of `dd`.
+/// A lone `B` means bytes, and suffixes are not case-sensitive.
+/// The number may have a fractional part, as in `1.5M`; the result is rounded down to a whole
+/// byte.
+///
+/// # Example
+///
+/// ```
+/// use getopt::parsers::ByteSize;
+///
+/// assert_eq!(Ok(ByteSize(65536)), "64K".parse());
+/// assert_eq!(Ok(ByteSize(2 * 1024 * 1024 * 1024)), "2GiB".parse());
+/// assert_eq!(Ok(ByteSize(1_500_000)), "1.5MB".parse());
+/// assert_eq!(Ok(ByteSize(100)), "100b".parse());
+/// assert!("64X".parse::<ByteSize>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ByteSize(pub u64);
+
+impl FromStr for ByteSize {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = ParseError::new("expected a size in bytes, like 512, 64K or 2GiB");
+        let (number, suffix) = split_number(string).ok_or_else(|| error.clone())?;
+
+        let suffix = suffix.to_ascii_uppercase();
+        let mut chars = suffix.chars();
+        let power = match chars.next() {
+            None | Some('B') if suffix.len() <= 1 => 0,
+            Some('K') => 1,
+            Some('M') => 2,
+            Some('G') => 3,
+            Some('T') => 4,
+            Some('P') => 5,
+            Some('E') => 6,
+            _ => return Err(error),
+        };
+        let base: f64 = match chars.as_str() {
+            "" | "IB" => 1024.0,
+            "B" => 1000.0,
+            _ => return Err(error),
+        };
+
+        let bytes = number * base.powi(power);
+        if !bytes.is_finite() || bytes >= u64::max_value() as f64 {
+            return Err(ParseError::new("size is too large"));
+        }
+        Ok(ByteSize(bytes as u64))
+    }
+}
+
+/// A percentage between 0 and 100, such as `50%` or `12.5`.
+///
+/// The `%` sign is optional.
+///
+/// # Example
+///
+/// ```
+/// use getopt::parsers::Percent;
+///
+/// assert_eq!(Ok(Percent(50.0)), "50%".parse());
+/// assert_eq!(0.125, "12.5".parse::<Percent>().unwrap().fraction());
+/// assert!("150%".parse::<Percent>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Percent(pub f64);
+
+impl Percent {
+    /// Returns the percentage as a fraction, from `0.0` to `1.0`.
+    pub fn fraction(self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+impl FromStr for Percent {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let number = if string.ends_with('%') {
+            &string[..string.len() - 1]
+        } else {
+            string
+        };
+
+        match split_number(number) {
+            Some((percent, "")) if percent <= 100.0 => Ok(Percent(percent)),
+            Some((_, "")) => Err(ParseError::new("percentage is over 100")),
+            _ => Err(ParseError::new("expected a percentage, like 50%")),
+        }
+    }
+}
+
+/// A yes-or-no answer, such as `yes`, `off` or `1`.
+///
+/// Accepted values are `yes`, `true`, `on` and `1`, or `no`, `false`, `off` and `0`, in any
+/// case.
+///
+/// # Example
+///
+/// ```
+/// use getopt::parsers::Bool;
+///
+/// assert_eq!(Ok(Bool(true)), "Yes".parse());
+/// assert_eq!(Ok(Bool(false)), "off".parse());
+/// assert!("maybe".parse::<Bool>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Bool(pub bool);
+
+impl Bool {
+    /// Interpret an argument that may be absent, where an absent argument means `true`.
+    ///
+    /// This suits options that may be given either as a plain flag or with an explicit answer,
+    /// where the option's argument is `None` for the flag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use getopt::{parsers::Bool, Opt};
+    ///
+    /// let opt = Opt('c', None);
+    /// assert_eq!(Ok(Bool(true)), Bool::from_arg(opt.1.as_deref()));
+    /// assert_eq!(Ok(Bool(false)), Bool::from_arg(Some("no")));
+    /// ```
+    pub fn from_arg(arg: Option<&str>) -> Result<Self, ParseError> {
+        match arg {
+            None => Ok(Bool(true)),
+            Some(arg) => arg.parse(),
+        }
+    }
+}
+
+impl FromStr for Bool {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.to_ascii_lowercase().as_str() {
+            "yes" | "true" | "on" | "1" => Ok(Bool(true)),
+            "no" | "false" | "off" | "0" => Ok(Bool(false)),
+            _ => Err(ParseError::new("expected yes or no")),
+        }
+    }
+}
+
+/// A `NAME=VALUE` pair, as given to compiler-style options like `-D`.
+///
+/// The pair is split at the first `=`, so the value may itself contain `=`; the value may be
+/// empty, but the name may not.
+///
+/// # Example
+///
+/// ```
+/// use getopt::parsers::KeyValue;
+///
+/// assert_eq!(Ok(KeyValue("a".to_string(), "b=c".to_string())), "a=b=c".parse());
+/// assert_eq!(Ok(KeyValue("a".to_string(), "".to_string())), "a=".parse());
+/// assert!("a".parse::<KeyValue>().is_err());
+/// assert!("=b".parse::<KeyValue>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct KeyValue(pub String, pub String);
+
+impl FromStr for KeyValue {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.find('=') {
+            None => Err(ParseError::new("expected NAME=VALUE")),
+            Some(0) => Err(ParseError::new("expected NAME=VALUE, but NAME is empty")),
+            Some(i) => Ok(KeyValue(
+                string[..i].to_string(),
+                string[i + 1..].to_string(),
+            )),
+        }
+    }
+}
+
+/// Collect the `NAME=VALUE` arguments of every occurrence of the option `opt` into a map.
+///
+/// When a name is given more than once, the last value wins.
+/// Returns an [`Error`](../struct.Error.html) of kind
+/// [`InvalidValue`](../enum.ErrorKind.html#variant.InvalidValue) for the first argument that is
+/// not a [`KeyValue`](struct.KeyValue.html) pair.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use getopt::{parsers, Parser};
+///
+/// // args = ["cc", "-DDEBUG=", "-O", "-D", "LEVEL=1", "-DLEVEL=2"];
+/// # let args: Vec<String> = vec!["cc", "-DDEBUG=", "-O", "-D", "LEVEL=1", "-DLEVEL=2"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let (opts, _) = Parser::new(&args, "D:O").into_parts()?;
+/// let defines = parsers::key_values(&opts, 'D')?;
+///
+/// assert_eq!(Some(""), defines.get("DEBUG").map(String::as_str));
+/// assert_eq!(Some("2"), defines.get("LEVEL").map(String::as_str));
+/// # Ok(())
+/// # }
+/// ```
+pub fn key_values<'a, I>(opts: I, opt: char) -> crate::Result<BTreeMap<String, String>>
+where
+    I: IntoIterator<Item = &'a Opt>,
+{
+    let mut map = BTreeMap::new();
+    for o in opts.into_iter().filter(|o| o.0 == opt) {
+        if let Some(KeyValue(key, value)) = o.parse_value()? {
+            map.insert(key, value);
+        }
+    }
+    Ok(map)
+}
@@ -0,0 +1,44 @@
+use crate::{opt::Opt, parser::Parser};
+
+/// Pick out the options in `bootstrap` from `args` ahead of the real parse, ignoring everything
+/// else.
+///
+/// This is for the common pattern of reading a few options first, such as the name of a
+/// configuration file or a verbosity flag, and only then parsing the full set of options.
+/// `args` is parsed exactly as [`Parser::new(args, optstring)`](struct.Parser.html#method.new)
+/// would parse it, so an option-argument that happens to look like a bootstrap option is skipped
+/// correctly; errors are skipped silently, since the full parse will report them.
+/// The options are returned in the order they were given.
+///
+/// Nothing is consumed: once the bootstrap options have been dealt with, a new
+/// [`Parser`](struct.Parser.html) can be created over the same `args`.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use getopt::{Opt, Parser};
+///
+/// // args = ["program", "-o", "-c", "-x", "-c", "my.conf", "-v", "file"];
+/// # let args: Vec<String> = vec!["program", "-o", "-c", "-x", "-c", "my.conf", "-v", "file"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let optstring = "c:o:vx";
+///
+/// let bootstrap = getopt::prescan(&args, optstring, "cv");
+/// assert_eq!(vec![Opt('c', Some("my.conf".to_string())), Opt('v', None)], bootstrap);
+///
+/// // ... load the configuration, then parse everything
+/// let (opts, operands) = Parser::new(&args, optstring).into_parts()?;
+/// assert_eq!(4, opts.len());
+/// assert_eq!(vec!["file"], operands);
+/// # Ok(())
+/// # }
+/// ```
+pub fn prescan(args: &[String], optstring: &str, bootstrap: &str) -> Vec<Opt> {
+    Parser::new(args, optstring)
+        .ok_only()
+        .filter(|&Opt(opt, _)| bootstrap.contains(opt))
+        .collect()
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::{error::Error, errorkind::ErrorKind, opt::Opt, parser::parse_optstring};
+
+/// How an option was laid out on the command line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Layout {
+    /// The option shares its element with the option before it, as `b` does in `-ab`.
+    pub grouped: bool,
+    /// The option's argument is in the same element as the option, as in `-bfoo`.
+    pub attached: bool,
+}
+
+/// Something [`PushParser`](struct.PushParser.html) found in its input.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Event {
+    /// A valid option, with its argument if it takes one.
+    Opt(Opt, Layout),
+    /// An invalid option, or an option missing its argument.
+    Error(Error, Layout),
+    /// The `--` argument marking the end of options.
+    Separator,
+    /// An argument which is not an option.
+    Operand(String),
+}
+
+/// A parser that is fed arguments one at a time, rather than pulling them from a vector.
+///
+/// `PushParser` follows the same rules as [`Parser`](struct.Parser.html), but suits callers
+/// that receive their arguments incrementally, such as interactive shells.
+/// Unlike `Parser`, there is no program name: the first argument pushed may be an option.
+///
+/// # Example
+///
+/// ```
+/// use getopt::{Event, Layout, Opt, PushParser};
+///
+/// let mut opts = PushParser::new("ab:");
+///
+/// assert_eq!(vec![Event::Opt(Opt('a', None), Layout::default())], opts.push_arg("-ab"));
+/// assert!(opts.is_waiting());
+/// assert_eq!(
+///     vec![Event::Opt(
+///         Opt('b', Some("foo".to_string())),
+///         Layout {
+///             grouped: true,
+///             attached: false,
+///         }
+///     )],
+///     opts.push_arg("foo")
+/// );
+/// assert_eq!(vec![Event::Operand("bar".to_string())], opts.push_arg("bar"));
+/// assert_eq!(vec![Event::Operand("-a".to_string())], opts.push_arg("-a"));
+/// assert!(opts.finish().is_empty());
+/// ```
+#[derive(Debug, Eq, PartialEq)]
+pub struct PushParser {
+    opts: HashMap<char, bool>,
+    pending: Option<(char, Layout)>,
+    done: bool,
+}
+
+impl PushParser {
+    /// Create a new `PushParser`, which will process arguments according to the options
+    /// specified in `optstring`, in the same format as for [`Parser::new`](struct.Parser.html#method.new).
+    pub fn new(optstring: &str) -> Self {
+        Self {
+            opts: parse_optstring(optstring),
+            pending: None,
+            done: false,
+        }
+    }
+
+    /// Return whether the last argument pushed ended with an option that is still waiting for
+    /// its argument.
+    pub fn is_waiting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Return whether option processing has ended, so that every further argument is an operand.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Process the next argument, returning whatever it contained.
+    ///
+    /// An argument may produce several events (for a group of options), or none at all (for an
+    /// option whose argument is expected in the next push).
+    pub fn push_arg(&mut self, arg: &str) -> Vec<Event> {
+        if let Some((opt, layout)) = self.pending.take() {
+            return vec![Event::Opt(Opt(opt, Some(arg.to_string())), layout)];
+        }
+
+        if self.done {
+            return vec![Event::Operand(arg.to_string())];
+        }
+
+        if arg == "--" {
+            self.done = true;
+            return vec![Event::Separator];
+        }
+
+        if !arg.starts_with('-') || arg == "-" {
+            self.done = true;
+            return vec![Event::Operand(arg.to_string())];
+        }
+
+        let mut events = Vec::new();
+        let mut chars = arg[1..].chars();
+        let mut layout = Layout::default();
+        while let Some(opt) = chars.next() {
+            match self.opts.get(&opt) {
+                None => events.push(Event::Error(
+                    Error::new(ErrorKind::UnknownOption, opt),
+                    layout,
+                )),
+                Some(false) => events.push(Event::Opt(Opt(opt, None), layout)),
+                Some(true) => {
+                    let rest = chars.as_str();
+                    if rest.is_empty() {
+                        self.pending = Some((opt, layout));
+                    } else {
+                        layout.attached = true;
+                        events.push(Event::Opt(Opt(opt, Some(rest.to_string())), layout));
+                    }
+                    break;
+                },
+            }
+            layout.grouped = true;
+        }
+        events
+    }
+
+    /// Signal the end of input, returning an error if an option is still waiting for its
+    /// argument.
+    pub fn finish(&mut self) -> Vec<Event> {
+        match self.pending.take() {
+            None => Vec::new(),
+            Some((opt, layout)) => {
+                vec![Event::Error(
+                    Error::new(ErrorKind::MissingArgument, opt),
+                    layout,
+                )]
+            },
+        }
+    }
+}
+
+/// Rebuild an argument vector from the events produced by [`PushParser`](struct.PushParser.html).
+///
+/// Given every event produced for a sequence of arguments, including those returned by
+/// [`finish`](struct.PushParser.html#method.finish), the result is identical to that sequence.
+///
+/// # Example
+///
+/// ```
+/// use getopt::{reconstruct, PushParser};
+///
+/// let args = ["-axbfoo", "-b", "bar", "--", "baz"];
+/// let mut opts = PushParser::new("ab:");
+/// let mut events = Vec::new();
+/// for arg in args.iter() {
+///     events.extend(opts.push_arg(arg));
+/// }
+/// events.extend(opts.finish());
+///
+/// assert_eq!(args.to_vec(), reconstruct(&events));
+/// ```
+pub fn reconstruct(events: &[Event]) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+
+    for event in events {
+        let (opt, arg, layout) = match *event {
+            Event::Opt(Opt(opt, ref arg), layout) => (opt, arg.as_ref(), layout),
+            Event::Error(ref error, layout) => (error.culprit(), None, layout),
+            Event::Separator => {
+                args.push("--".to_string());
+                continue;
+            },
+            Event::Operand(ref operand) => {
+                args.push(operand.clone());
+                continue;
+            },
+        };
+
+        match args.last_mut() {
+            Some(last) if layout.grouped => last.push(opt),
+            _ => args.push(format!("-{}", opt)),
+        }
+
+        if let Some(arg) = arg {
+            match args.last_mut() {
+                Some(last) if layout.attached => last.push_str(arg),
+                _ => args.push(arg.clone()),
+            }
+        }
+    }
+
+    args
+}
@@ -0,0 +1,548 @@
+//! Helpers for dealing with shell command-line syntax.
+
+use std::{iter::Peekable, str};
+
+use crate::{error::Error, errorkind::ErrorKind, result::Result};
+
+/// A family of shells that share a quoting syntax.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Shell {
+    /// Bourne-style shells that also understand ANSI-C `$'...'` strings: bash, ksh and zsh.
+    AnsiC,
+    /// The Bourne shell and its descendants: sh, ash, dash, bash, ksh, mksh, zsh, etc.
+    Bourne,
+    /// The C shell and tcsh.
+    C,
+    /// The friendly interactive shell, fish.
+    Fish,
+    /// The Plan 9 shell, rc.
+    Rc,
+}
+
+/// Quote a string so that `shell` reads it back as a single, literal word.
+///
+/// With [`Shell::AnsiC`](enum.Shell.html#variant.AnsiC), `$'...'` is only used when the string
+/// contains control characters, which are then spelled out as escapes (the C1 controls, from
+/// U+0080 to U+009F, as the `\xHH` escapes of their UTF-8 bytes);
+/// otherwise the string is quoted as for [`Shell::Bourne`](enum.Shell.html#variant.Bourne).
+/// Every other shell keeps control characters verbatim.
+///
+/// # Example
+///
+/// ```
+/// use getopt::shell::{quote, Shell};
+///
+/// assert_eq!(r"'it'\''s'", quote("it's", Shell::Bourne));
+/// assert_eq!(r"'it''s'", quote("it's", Shell::Rc));
+/// assert_eq!(r"$'a\tb'", quote("a\tb", Shell::AnsiC));
+/// ```
+pub fn quote(string: &str, shell: Shell) -> String {
+    match shell {
+        // bash, ksh and zsh also understand $'...', which can spell out control characters
+        Shell::AnsiC => {
+            if !string.chars().any(char::is_control) {
+                return quote(string, Shell::Bourne);
+            }
+
+            let mut new_string = String::from("$'");
+            for c in string.chars() {
+                match c {
+                    '\\' | '\'' => {
+                        new_string.push('\\');
+                        new_string.push(c);
+                    },
+                    '\u{07}' => new_string.push_str("\\a"),
+                    '\u{08}' => new_string.push_str("\\b"),
+                    '\u{0C}' => new_string.push_str("\\f"),
+                    '\n' => new_string.push_str("\\n"),
+                    '\r' => new_string.push_str("\\r"),
+                    '\t' => new_string.push_str("\\t"),
+                    '\u{0B}' => new_string.push_str("\\v"),
+                    // \xHH reads at most two hex digits, so the next character is safe; C1
+                    // controls are spelled out as their UTF-8 bytes, which unlike \u do not
+                    // depend on the shell's locale
+                    c if c.is_control() => {
+                        let mut bytes = [0; 4];
+                        for b in c.encode_utf8(&mut bytes).bytes() {
+                            new_string.push_str(&format!("\\x{:02x}", b));
+                        }
+                    },
+                    _ => new_string.push(c),
+                }
+            }
+            new_string.push('\'');
+            new_string
+        },
+
+        // most shells (sh, ksh, zsh, bash, (d)ash, etc.) are in this category
+        Shell::Bourne => {
+            let e = '\\'; // escape char
+            let q = '\''; // quote char
+            let mut new_string = String::new();
+            new_string.push(q);
+            for c in string.chars() {
+                match c {
+                    '\'' => {
+                        new_string.push(q);
+                        new_string.push(e);
+                        new_string.push(c);
+                        new_string.push(q);
+                    },
+                    _ => new_string.push(c),
+                }
+            }
+            new_string.push(q);
+            new_string
+        },
+
+        Shell::C => {
+            let e = '\\'; // escape char
+            let q = '\''; // quote char
+            let mut new_string = String::new();
+            new_string.push(q);
+            for c in string.chars() {
+                match c {
+                    ' ' | '\'' => {
+                        new_string.push(q);
+                        new_string.push(e);
+                        new_string.push(c);
+                        new_string.push(q);
+                    },
+                    _ => new_string.push(c),
+                }
+            }
+            new_string.push(q);
+            new_string
+        },
+
+        Shell::Fish => {
+            let e = '\\'; // escape char
+            let q = '\''; // quote char
+            let mut new_string = String::new();
+            new_string.push(q);
+            for c in string.chars() {
+                match c {
+                    '\'' | '\\' => {
+                        new_string.push(e);
+                        new_string.push(c);
+                    },
+                    _ => new_string.push(c),
+                }
+            }
+            new_string.push(q);
+            new_string
+        },
+
+        Shell::Rc => {
+            let q = '\''; // quote char
+            let mut new_string = String::new();
+            new_string.push(q);
+            for c in string.chars() {
+                match c {
+                    '\'' => {
+                        new_string.push(q);
+                        new_string.push(c);
+                    },
+                    _ => new_string.push(c),
+                }
+            }
+            new_string.push(q);
+            new_string
+        },
+    }
+}
+
+/// Reverse [`quote`](fn.quote.html), reading `string` as a single word in `shell`'s syntax.
+///
+/// Quoted and unquoted parts may be freely concatenated, as they can in the shell itself; no
+/// expansions are performed, and unquoted whitespace is kept as it is.
+/// For each shell, the recognised quoting is:
+///   - `Bourne`: `'...'`, `"..."` (where a backslash only escapes `$`, `` ` ``, `"`, `\` and
+///     newline) and a backslash before any character;
+///   - `AnsiC`: as `Bourne`, plus `$'...'` with the usual C escapes, `\xHH` and `\NNN`, where
+///     a run of escaped bytes is decoded as UTF-8 if it is valid, and otherwise as Latin-1;
+///   - `C`: `'...'`, `"..."` (with no escapes at all) and a backslash before any character;
+///   - `Fish`: `'...'` (where only `\'` and `\\` are escapes), `"..."` (where only `\"`, `\$`,
+///     `\\` and newline are) and a backslash before any character;
+///   - `Rc`: `'...'`, in which `''` stands for a single quote.
+///
+/// Returns an [`Error`](../struct.Error.html) of kind
+/// [`UnmatchedQuote`](../enum.ErrorKind.html#variant.UnmatchedQuote) if a quote is left open.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use getopt::shell::{quote, unquote, Shell};
+///
+/// assert_eq!("it's", unquote(r"'it'\''s'", Shell::Bourne)?);
+/// assert_eq!("a\tb", unquote(r"$'a\tb'", Shell::AnsiC)?);
+/// assert_eq!("it's", unquote(&quote("it's", Shell::Rc), Shell::Rc)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn unquote(string: &str, shell: Shell) -> Result<String> {
+    let mut new_string = String::new();
+    let mut chars = string.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (shell, c) {
+            (Shell::Rc, '\'') => loop {
+                match chars.next() {
+                    None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        new_string.push('\'');
+                    },
+                    Some('\'') => break,
+                    Some(c) => new_string.push(c),
+                }
+            },
+            (Shell::Rc, _) => new_string.push(c),
+
+            (_, '\\') => match chars.next() {
+                // a backslash-newline pair is a line continuation
+                Some('\n') => (),
+                Some(c) => new_string.push(c),
+                None => new_string.push(c),
+            },
+            (Shell::AnsiC, '$') if chars.peek() == Some(&'\'') => {
+                chars.next();
+                unquote_ansi_c(&mut chars, &mut new_string)?;
+            },
+            (Shell::Fish, '\'') => loop {
+                match chars.next() {
+                    None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                    Some('\'') => break,
+                    Some('\\') => match chars.peek() {
+                        Some(&e @ '\'') | Some(&e @ '\\') => {
+                            chars.next();
+                            new_string.push(e);
+                        },
+                        _ => new_string.push('\\'),
+                    },
+                    Some(c) => new_string.push(c),
+                }
+            },
+            (_, '\'') => loop {
+                match chars.next() {
+                    None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                    Some('\'') => break,
+                    Some(c) => new_string.push(c),
+                }
+            },
+            (_, '"') => {
+                let escapes: &[char] = match shell {
+                    Shell::C => &[],
+                    Shell::Fish => &['"', '$', '\\', '\n'],
+                    _ => &['$', '`', '"', '\\', '\n'],
+                };
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                        Some('"') => break,
+                        Some('\\') => match chars.peek() {
+                            Some(&e) if escapes.contains(&e) => {
+                                chars.next();
+                                if e != '\n' {
+                                    new_string.push(e);
+                                }
+                            },
+                            _ => new_string.push('\\'),
+                        },
+                        Some(c) => new_string.push(c),
+                    }
+                }
+            },
+            _ => new_string.push(c),
+        }
+    }
+
+    Ok(new_string)
+}
+
+// Read the body of a $'...' string, up to and including the closing quote.
+fn unquote_ansi_c<I>(chars: &mut Peekable<I>, new_string: &mut String) -> Result<()>
+where
+    I: Iterator<Item = char>,
+{
+    // bytes given as \x or octal escapes, decoded together so that they may spell out UTF-8
+    let mut bytes = Vec::new();
+    loop {
+        let c = chars.next();
+        match c {
+            Some('\\') if chars.peek().map_or(false, |&e| e == 'x' || e.is_digit(8)) => (),
+            _ => push_bytes(&mut bytes, new_string),
+        }
+        match c {
+            None => return Err(Error::new(ErrorKind::UnmatchedQuote, '\'')),
+            Some('\'') => return Ok(()),
+            Some('\\') => match chars.next() {
+                None => return Err(Error::new(ErrorKind::UnmatchedQuote, '\'')),
+                Some('a') => new_string.push('\u{07}'),
+                Some('b') => new_string.push('\u{08}'),
+                Some('e') | Some('E') => new_string.push('\u{1B}'),
+                Some('f') => new_string.push('\u{0C}'),
+                Some('n') => new_string.push('\n'),
+                Some('r') => new_string.push('\r'),
+                Some('t') => new_string.push('\t'),
+                Some('v') => new_string.push('\u{0B}'),
+                Some(e @ '\\') | Some(e @ '\'') | Some(e @ '"') | Some(e @ '?') => {
+                    new_string.push(e)
+                },
+                // bash keeps only the low byte
+                Some('x') => match take_digits(chars, 16, 2, None) {
+                    // a lone \x has nothing to encode, so it stands for itself
+                    None => {
+                        push_bytes(&mut bytes, new_string);
+                        new_string.push_str("\\x");
+                    },
+                    Some(value) => bytes.push(value as u8),
+                },
+                Some(d @ '0'..='7') => {
+                    let value = take_digits(chars, 8, 2, d.to_digit(8)).unwrap_or(0);
+                    bytes.push(value as u8);
+                },
+                Some(e) => {
+                    new_string.push('\\');
+                    new_string.push(e);
+                },
+            },
+            Some(c) => new_string.push(c),
+        }
+    }
+}
+
+// Decode escaped bytes as UTF-8 where they are valid, and any others as Latin-1, which is all a
+// String can hold them as.
+fn push_bytes(bytes: &mut Vec<u8>, new_string: &mut String) {
+    let mut rest = &bytes[..];
+    while !rest.is_empty() {
+        match str::from_utf8(rest) {
+            Ok(string) => {
+                new_string.push_str(string);
+                break;
+            },
+            Err(error) => {
+                let (valid, invalid) = rest.split_at(error.valid_up_to());
+                new_string.push_str(str::from_utf8(valid).unwrap_or_default());
+                new_string.push(char::from(invalid[0]));
+                rest = &invalid[1..];
+            },
+        }
+    }
+    bytes.clear();
+}
+
+// Read up to `max` digits in `radix`, accumulating onto `value`.
+fn take_digits<I>(
+    chars: &mut Peekable<I>,
+    radix: u32,
+    max: usize,
+    mut value: Option<u32>,
+) -> Option<u32>
+where
+    I: Iterator<Item = char>,
+{
+    for _ in 0..max {
+        match chars.peek().and_then(|c| c.to_digit(radix)) {
+            None => break,
+            Some(digit) => {
+                chars.next();
+                value = Some(value.unwrap_or(0) * radix + digit);
+            },
+        }
+    }
+    value
+}
+
+/// Split a string into fields the way a POSIX shell would, honouring quotes and backslashes.
+///
+/// No expansions of any kind are performed; `$`, `` ` ``, `*` and friends are kept verbatim.
+/// Quoting follows the rules of the shell command language:
+///   - outside of quotes, a backslash preserves the literal value of the following character,
+///     and a backslash-newline pair is removed entirely;
+///   - inside single quotes, every character is literal;
+///   - inside double quotes, a backslash only escapes `$`, `` ` ``, `"`, `\` and newline.
+///
+/// Returns an [`Error`](../struct.Error.html) of kind
+/// [`UnmatchedQuote`](../enum.ErrorKind.html#variant.UnmatchedQuote) if a quote is left open.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let args = getopt::shell::split(r#"-a 'b c' "d \"e\"" f\ g"#)?;
+///
+/// assert_eq!(vec!["-a", "b c", "d \"e\"", "f g"], args);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split(string: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    // distinguishes an empty quoted field ('') from no field at all
+    let mut in_field = false;
+    let mut chars = string.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_field {
+                    fields.push(field.split_off(0));
+                    in_field = false;
+                }
+            },
+            '\\' => match chars.next() {
+                Some('\n') => (),
+                Some(c) => {
+                    field.push(c);
+                    in_field = true;
+                },
+                // a trailing backslash has nothing to escape, so it stands for itself
+                None => {
+                    field.push(c);
+                    in_field = true;
+                },
+            },
+            '\'' => {
+                in_field = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                        Some('\'') => break,
+                        Some(c) => field.push(c),
+                    }
+                }
+            },
+            '"' => {
+                in_field = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            None => return Err(Error::new(ErrorKind::UnmatchedQuote, c)),
+                            Some('\n') => (),
+                            Some(e @ '$') | Some(e @ '`') | Some(e @ '"') | Some(e @ '\\') => {
+                                field.push(e)
+                            },
+                            Some(e) => {
+                                field.push('\\');
+                                field.push(e);
+                            },
+                        },
+                        Some(c) => field.push(c),
+                    }
+                }
+            },
+            _ => {
+                field.push(c);
+                in_field = true;
+            },
+        }
+    }
+
+    if in_field {
+        fields.push(field);
+    }
+
+    Ok(fields)
+}
+
+/// Split a Windows command line into arguments, following the rules used by the Microsoft C
+/// runtime and `CommandLineToArgvW`.
+///
+/// The first argument is the program name, which is delimited by whitespace or by a pair of
+/// double quotes, with no escaping.
+/// For every other argument:
+///   - `2n` backslashes followed by a `"` produce `n` backslashes, and the quote toggles quoting;
+///   - `2n + 1` backslashes followed by a `"` produce `n` backslashes and a literal `"`;
+///   - backslashes not followed by a `"` are literal;
+///   - inside quotes, `""` produces a literal `"`.
+///
+/// Unlike [`split`](fn.split.html), this cannot fail: an unterminated quote simply extends to
+/// the end of the string.
+///
+/// # Example
+///
+/// ```
+/// let args = getopt::shell::split_windows(r#""C:\Program Files\x.exe" -a "b c" d\"e f\\"#);
+///
+/// assert_eq!(vec![r"C:\Program Files\x.exe", "-a", "b c", "d\"e", r"f\\"], args);
+/// ```
+pub fn split_windows(string: &str) -> Vec<String> {
+    let chars: Vec<char> = string.chars().collect();
+    let len = chars.len();
+    let mut args = Vec::new();
+    let mut i = 0;
+
+    // the program name gets no backslash processing at all
+    if len > 0 {
+        let mut arg = String::new();
+        if chars[0] == '"' {
+            i = 1;
+            while i < len && chars[i] != '"' {
+                arg.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+        } else {
+            while i < len && chars[i] != ' ' && chars[i] != '\t' {
+                arg.push(chars[i]);
+                i += 1;
+            }
+        }
+        args.push(arg);
+    }
+
+    loop {
+        while i < len && (chars[i] == ' ' || chars[i] == '\t') {
+            i += 1;
+        }
+        if i >= len {
+            break;
+        }
+
+        let mut arg = String::new();
+        let mut quoted = false;
+        while i < len {
+            match chars[i] {
+                ' ' | '\t' if !quoted => break,
+                '\\' => {
+                    let mut n = 0;
+                    while i < len && chars[i] == '\\' {
+                        n += 1;
+                        i += 1;
+                    }
+                    if i < len && chars[i] == '"' {
+                        arg.extend(vec!['\\'; n / 2]);
+                        if n % 2 == 1 {
+                            arg.push('"');
+                            i += 1;
+                        }
+                    } else {
+                        arg.extend(vec!['\\'; n]);
+                    }
+                },
+                '"' => {
+                    if quoted && i + 1 < len && chars[i + 1] == '"' {
+                        arg.push('"');
+                        i += 1;
+                    } else {
+                        quoted = !quoted;
+                    }
+                    i += 1;
+                },
+                c => {
+                    arg.push(c);
+                    i += 1;
+                },
+            }
+        }
+        args.push(arg);
+    }
+
+    args
+}
@@ -0,0 +1,91 @@
+#[cfg(unix)]
+use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+/// Find where the options in `args` end, without checking them against any optstring.
+///
+/// Returns the index of the first operand, following the same rules as
+/// [`Parser`](struct.Parser.html): scanning starts at the second element of `args`, and stops at
+/// the first element that is not hyphenated, at the first `-`, or after the first `--`.
+/// The index is never past the end of `args`, so `args[index..]` is always valid.
+///
+/// As no optstring is consulted, an option-argument given as a separate element is taken to be
+/// the first operand; this suits wrappers which only need to find what follows `--`.
+///
+/// # Example
+///
+/// ```
+/// // args = ["wrapper", "-v", "--", "child", "-x"];
+/// # let args: Vec<String> = vec!["wrapper", "-v", "--", "child", "-x"]
+/// #     .into_iter()
+/// #     .map(String::from)
+/// #     .collect();
+/// let index = getopt::split_args(&args);
+///
+/// assert_eq!(3, index);
+/// assert_eq!(["child", "-x"], args[index..]);
+/// ```
+pub fn split_args(args: &[String]) -> usize {
+    let mut index = 1;
+
+    while index < args.len() {
+        let arg = &args[index];
+        if arg == "--" {
+            return index + 1;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            break;
+        }
+        index += 1;
+    }
+
+    // an empty `args` has no second element to start from
+    index.min(args.len())
+}
+
+/// Split a buffer of NUL-terminated arguments, as read from `/proc/<pid>/cmdline`, into an
+/// argument vector.
+///
+/// Only available on Unix.
+///
+/// Arguments are returned as `OsString`s, so nothing is lost if they are not valid Unicode; they
+/// can be converted with [`OsString::into_string`] before parsing, as
+/// [`cli::args_checked`](cli/fn.args_checked.html) does for the program's own arguments.
+/// The final argument need not be terminated, and an empty buffer yields no arguments.
+///
+/// [`OsString::into_string`]: https://doc.rust-lang.org/std/ffi/struct.OsString.html#method.into_string
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use getopt::{Opt, Parser};
+///
+/// let cmdline = b"ls\0-l\0\0-a\0";
+/// let args = getopt::split_nul(cmdline)
+///     .into_iter()
+///     .map(|arg| arg.into_string())
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("arguments are not valid Unicode");
+/// assert_eq!(vec!["ls", "-l", "", "-a"], args);
+///
+/// let (opts, operands) = Parser::new(&args, "al").into_parts()?;
+/// assert_eq!(vec![Opt('l', None)], opts);
+/// assert_eq!(vec!["", "-a"], operands);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(unix)]
+pub fn split_nul(buf: &[u8]) -> Vec<OsString> {
+    if buf.is_empty() {
+        return Vec::new();
+    }
+    let buf = if buf.last() == Some(&0) {
+        &buf[..buf.len() - 1]
+    } else {
+        buf
+    };
+
+    buf.split(|&b| b == 0)
+        .map(|arg| OsString::from_vec(arg.to_vec()))
+        .collect()
+}
@@ -0,0 +1,16 @@
+/// Why [`Parser`](struct.Parser.html) stopped parsing options.
+///
+/// New reasons may be added in future releases, so matches against this enum must include a
+/// wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum StopReason {
+    /// There were no arguments left.
+    End,
+    /// A `--` delimiter was found.
+    Separator,
+    /// A lone `-` was found; by convention, it names standard input or output.
+    Dash,
+    /// An operand was found.
+    Operand,
+}
@@ -0,0 +1,23 @@
+/// Build a `Vec<String>` from a list of string-like expressions, for use as an argument vector.
+///
+/// Only available with the `test-util` feature.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate getopt;
+/// # fn main() {
+/// use getopt::{Opt, Parser};
+///
+/// let args = args!["program", "-a", "foo"];
+/// let mut opts = Parser::new(&args, "a:");
+///
+/// assert_eq!(Some(Ok(Opt('a', Some("foo".to_string())))), opts.next());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! args {
+    ($($arg:expr),* $(,)?) => {
+        vec![$(::std::string::String::from($arg)),*]
+    };
+}
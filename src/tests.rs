@@ -1,4 +1,9 @@
-use crate::{Opt, Parser};
+use std::io;
+
+use crate::{
+    cli, extract, reconstruct, shell, split_args, Class, Dialect, Error, ErrorKind, Event,
+    Guideline, Layout, Lexer, Opt, Parser, PushParser, StopReason, Token, Warning, WarningKind,
+};
 
 macro_rules! basic_test {
     ($name:ident, $expect:expr, $next:expr, [$($arg:expr),+], $optstr:expr) => (
@@ -70,8 +75,8 @@ macro_rules! error_test {
     )
 }
 
-#[rustfmt::skip] error_test!(bad_opt, "unknown option -- 'b'", ["x", "-b"], "a");
-#[rustfmt::skip] error_test!(missing_optarg, "option requires an argument -- 'a'", ["x", "-a"], "a:");
+#[rustfmt::skip] error_test!(bad_opt, "unknown option -- 'b' in '-b' (argument 1)", ["x", "-b"], "a");
+#[rustfmt::skip] error_test!(missing_optarg, "option requires an argument -- 'a' in '-a' (argument 1)", ["x", "-a"], "a:");
 
 #[test]
 fn multiple() -> Result<(), String> {
@@ -105,6 +110,167 @@ fn multiple() -> Result<(), String> {
     Ok(())
 }
 
+#[test]
+fn multibyte() {
+    let args: Vec<String> = vec!["x", "-éaßxy", "-ü", "ö", "-é"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "aéü:ß:");
+
+    assert_eq!(Some(Ok(Opt('é', None))), opts.next());
+    assert_eq!((1, 2), opts.position());
+    assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    assert_eq!(Some(Ok(Opt('ß', Some("xy".to_string())))), opts.next());
+    assert_eq!(Some(Ok(Opt('ü', Some("ö".to_string())))), opts.next());
+    assert_eq!(Some(Ok(Opt('é', None))), opts.next());
+    assert_eq!(None, opts.next());
+}
+
+#[test]
+fn into_operands_past_end() {
+    let args: Vec<String> = vec!["x".to_string()];
+    let mut opts = Parser::from_vec(args, "a");
+
+    assert_eq!(None, opts.next());
+    assert!(opts.into_operands().is_empty());
+}
+
+#[test]
+fn into_remaining_multibyte() {
+    let args: Vec<String> = vec!["x", "-éaß", "y"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let mut opts = Parser::new(&args, "éaß");
+    opts.next();
+    assert_eq!(vec!["-aß", "y"], opts.into_remaining());
+
+    let mut opts = Parser::new(&args, "éaß");
+    opts.by_ref().take(3).for_each(drop);
+    assert_eq!(vec!["y"], opts.into_remaining());
+}
+
+#[test]
+fn positions_multibyte() {
+    let args: Vec<String> = vec!["x", "-éaßxy", "-", "-ü", "ö"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "aéü:ß:");
+    opts.set_dash_option(true);
+
+    let found: Vec<_> = opts
+        .with_positions()
+        .map(|l| l.map(|l| (l.opt().0, l.index(), l.offset(), l.value_index())))
+        .collect();
+    assert_eq!(
+        vec![
+            Ok(('é', 1, 1, None)),
+            Ok(('a', 1, 2, None)),
+            Ok(('ß', 1, 3, Some(1))),
+            Ok(('-', 2, 0, None)),
+            Ok(('ü', 3, 1, Some(4))),
+        ],
+        found
+    );
+}
+
+#[test]
+fn stop_reasons() {
+    let cases: &[(&[&str], StopReason)] = &[
+        (&["x", "-a"], StopReason::End),
+        (&["x", "-a", "--", "-a"], StopReason::Separator),
+        (&["x", "-", "-a"], StopReason::Dash),
+        (&["x", "-a", "foo"], StopReason::Operand),
+    ];
+    for &(args, reason) in cases {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut opts = Parser::new(&args, "a");
+        while opts.next().is_some() {}
+        assert_eq!(Some(reason), opts.stop_reason(), "{:?}", args);
+    }
+
+    let args: Vec<String> = vec!["x", "-", "foo"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "a");
+    opts.set_dash_option(true);
+    assert_eq!(Some(Ok(Opt('-', None))), opts.next());
+    assert_eq!(None, opts.stop_reason());
+    assert_eq!(None, opts.next());
+    assert_eq!(Some(StopReason::Operand), opts.stop_reason());
+}
+
+#[test]
+fn classify_dash() {
+    let mut opts = Parser::new(&[], "a");
+    assert_eq!(Class::Operand, opts.classify("-"));
+    opts.set_dash_option(true);
+    assert_eq!(Class::Options, opts.classify("-"));
+    assert_eq!(Class::Options, opts.classify("-a"));
+}
+
+#[test]
+fn classify_clusters() {
+    let mut opts = Parser::new(&[], "ab:");
+    assert_eq!(Class::NeedsArgument('b'), opts.classify("-ab"));
+    assert_eq!(Class::Options, opts.classify("-abx"));
+    // the rest of the cluster is the argument, even if it looks like options
+    assert_eq!(Class::Options, opts.classify("-ba"));
+    assert_eq!(Class::Options, opts.classify("-bx"));
+    assert_eq!(Class::Unknown('x'), opts.classify("-axb"));
+
+    opts.set_guideline(Guideline::Grouping, false);
+    assert_eq!(Class::Unexpected('a'), opts.classify("-ab"));
+    assert_eq!(Class::Options, opts.classify("-bax"));
+    assert_eq!(Class::Options, opts.classify("-a"));
+}
+
+#[test]
+fn debug_report() {
+    let args: Vec<String> = vec!["x", "-ba", "-éa"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "ab:é");
+    opts.by_ref().take(2).for_each(drop);
+
+    let expect = concat!(
+        "optstring: ab:é\n",
+        "position:  index 2, point 2 (of 3 arguments)\n",
+        "current:   -éa\n",
+        "             ^\n",
+        "seen:      'b' x1, 'é' x1\n",
+    );
+    assert_eq!(expect, opts.debug_report());
+
+    opts.next();
+    assert!(opts.debug_report().contains("current:   (none)\n"));
+}
+
+#[test]
+fn matcher_macros() {
+    let args: Vec<String> = vec!["x", "-ab", "foo", "-c"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut matched = Vec::new();
+
+    for opt in Parser::new(&args, "ab:c") {
+        matched.push(match opt.unwrap() {
+            flag!('a') | flag!('c') => "flag".to_string(),
+            optarg!('b', ref arg) if arg == "bar" => unreachable!(),
+            optarg!('b', arg) => arg,
+            _ => unreachable!(),
+        });
+    }
+
+    assert_eq!(vec!["flag", "foo", "flag"], matched);
+}
+
 #[test]
 fn continue_after_error() {
     let args: Vec<String> = vec!["x", "-z", "-abc"]
@@ -116,3 +282,841 @@ fn continue_after_error() {
         // do nothing, should not panic
     }
 }
+
+#[test]
+fn no_delimiter() {
+    let args: Vec<String> = vec!["x", "-a", "--", "-a"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "a");
+    opts.set_guideline(Guideline::Delimiter, false);
+    assert!(!opts.guideline(Guideline::Delimiter));
+
+    assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    assert_eq!(None, opts.next());
+    assert_eq!(2, opts.index());
+}
+
+#[test]
+fn no_grouping() {
+    let args: Vec<String> = vec!["x", "-bfoo", "-za", "-a"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "ab:");
+    opts.set_guideline(Guideline::Grouping, false);
+
+    assert_eq!(Some(Ok(Opt('b', Some("foo".to_string())))), opts.next());
+    assert_eq!(
+        ErrorKind::UnknownOption,
+        opts.next().unwrap().unwrap_err().kind()
+    );
+    assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    assert_eq!(None, opts.next());
+}
+
+#[test]
+fn require_first() {
+    let args: Vec<String> = vec!["x", "-0", "-0a", "-0"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "0a");
+    opts.require_first('0');
+
+    assert_eq!(Some(Ok(Opt('0', None))), opts.next());
+    assert_eq!(Some(Ok(Opt('0', None))), opts.next());
+    assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    assert_eq!(
+        "option out of order -- '0' in '-0' (argument 3)",
+        opts.next().unwrap().unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn deprecated() {
+    let args: Vec<String> = vec!["x", "-abc", "-b", "foo", "-d"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "ab:");
+    opts.set_deprecated('a', None);
+    opts.set_deprecated('b', Some('B'));
+    opts.set_deprecated('d', None);
+
+    assert_eq!(4, opts.by_ref().count());
+    assert_eq!(
+        &[
+            Warning::new(WarningKind::Deprecated, 'a'),
+            Warning::deprecated('b', Some('B')),
+            Warning::deprecated('b', Some('B')),
+            Warning::new(WarningKind::Duplicate, 'b'),
+        ],
+        opts.warnings()
+    );
+    assert_eq!("deprecated option -- 'a'", opts.warnings()[0].to_string());
+}
+
+#[test]
+fn suspicious_usage() {
+    let args: Vec<String> = vec!["x", "--ab", "-o1", "-aa", "-o", "2", "-p1", "-p2"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "-ao:p:");
+    opts.set_max_occurrences('p', 1);
+
+    assert_eq!(9, opts.by_ref().count());
+    assert_eq!(
+        &[
+            Warning::new(WarningKind::LongOptionLike, '-'),
+            Warning::new(WarningKind::Duplicate, 'o'),
+        ],
+        opts.warnings()
+    );
+    assert_eq!(
+        "long option parsed as short options -- '-'",
+        opts.warnings()[0].to_string()
+    );
+    assert_eq!(
+        "option given more than once -- 'o'",
+        opts.warnings()[1].to_string()
+    );
+}
+
+#[test]
+fn collect_map_error() {
+    let args: Vec<String> = vec!["x", "-a", "-z", "-a"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "a");
+
+    assert_eq!(
+        "unknown option -- 'z' in '-z' (argument 2)",
+        opts.collect_map().unwrap_err().to_string()
+    );
+    assert_eq!(3, opts.index());
+}
+
+#[test]
+fn error_into_io_error() {
+    let mut error = Error::new(ErrorKind::UnknownOption, 'x');
+    error.set_program_name("prog");
+    assert_eq!(2, error.exit_code());
+
+    let error: io::Error = error.into();
+    assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+    assert_eq!("prog: unknown option -- 'x'", error.to_string());
+}
+
+#[test]
+fn pass_through_many() {
+    let mut args = vec!["x".to_string()];
+    args.extend((0..100_000).map(|_| "-x".to_string()));
+    args.push("-a".to_string());
+    let mut opts = Parser::from_vec(args, "a");
+    opts.set_pass_through(true);
+
+    assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+    assert_eq!(None, opts.next());
+    assert_eq!(100_000, opts.passed_through().len());
+}
+
+#[test]
+fn parse_known_many() -> Result<(), Error> {
+    let mut args = vec!["x".to_string()];
+    args.extend((0..100_000).map(|i| format!("-{}", if i % 2 == 0 { "x" } else { "ay" })));
+    args.push("z".to_string());
+    let mut opts = Parser::from_vec(args, "a");
+
+    let (known, unknown) = opts.parse_known()?;
+    assert_eq!(50_000, known.len());
+    assert_eq!(100_001, unknown.len());
+    assert_eq!(Some("-y"), unknown.get(1).map(String::as_str));
+    assert_eq!(Some("z"), unknown.last().map(String::as_str));
+    Ok(())
+}
+
+#[test]
+fn error_tokens() {
+    let args: Vec<String> = vec!["x", "-v", "-z", "-a", "1", "-a2", "-b"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "a:b:");
+    opts.set_pass_through(true);
+    opts.set_max_occurrences('a', 1);
+
+    let mut errors = opts.by_ref().filter_map(|r| r.err());
+    let error = errors.next().unwrap();
+    assert_eq!((Some("-a2"), Some(5)), (error.token(), error.index()));
+    let error = errors.next().unwrap();
+    assert_eq!(
+        "option requires an argument -- 'b' in '-b' (argument 6)",
+        error.to_string()
+    );
+    assert_eq!("option requires an argument -- 'b'", format!("{:#}", error));
+    let mut error = error;
+    error.set_dialect(Dialect::Bsd);
+    assert_eq!("option requires an argument -- b", error.to_string());
+
+    // the argument counts towards equality
+    let mut error = Error::new(ErrorKind::UnknownOption, 'x');
+    assert!(error.token().is_none());
+    let plain = Error::new(ErrorKind::UnknownOption, 'x');
+    assert_eq!(plain, error);
+    error.set_token("-ax", 1);
+    assert_ne!(plain, error);
+}
+
+#[test]
+fn occurrences_rejected() {
+    let args: Vec<String> = vec!["x", "-n1", "-nx", "-m", "-m", "-q", "-n"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "mn:");
+    opts.set_validator('n', |v| {
+        v.parse::<u8>().map(drop).map_err(|e| e.to_string())
+    });
+    opts.set_max_occurrences('m', 1);
+    assert_eq!(4, opts.by_ref().filter(Result::is_err).count());
+
+    assert_eq!(2, opts.occurrences('n'));
+    assert_eq!(2, opts.occurrences('m'));
+    assert_eq!(0, opts.occurrences('q'));
+}
+
+#[test]
+fn validator_closure() {
+    let args: Vec<String> = vec!["x", "-p80", "-p", "8080", "-p443"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let allowed = vec![80u16, 443];
+    let mut opts = Parser::new(&args, "p:");
+    opts.set_validator('p', move |v| match v.parse() {
+        Ok(port) if allowed.contains(&port) => Ok(()),
+        _ => Err(format!("not one of {:?}", allowed)),
+    });
+
+    let found: Vec<_> = opts.map(|r| r.map_err(|e| e.to_string())).collect();
+    assert_eq!(
+        vec![
+            Ok(Opt('p', Some("80".to_string()))),
+            Err(
+                "invalid value \"8080\" for option -- 'p' in '-p' (argument 2): \
+                 not one of [80, 443]"
+                    .to_string()
+            ),
+            Ok(Opt('p', Some("443".to_string()))),
+        ],
+        found
+    );
+}
+
+#[test]
+fn validator_equality() {
+    let args: Vec<String> = vec!["x", "-a1"].into_iter().map(String::from).collect();
+    let mut p1 = Parser::new(&args, "a:");
+    let mut p2 = Parser::new(&args, "a:");
+    let p3 = Parser::new(&args, "a:");
+    p1.set_validator('a', |_| Ok(()));
+    p2.set_validator('a', |_| Err("no".into()));
+
+    // validators cannot be compared, so only their presence is
+    assert_eq!(p1, p2);
+    assert_ne!(p1, p3);
+    assert_ne!(p1.next(), p2.next());
+}
+
+#[test]
+fn help_hint() {
+    let hint = |args: &[&str]| {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut opts = Parser::new(&args, "h");
+        opts.set_help_option('h');
+        opts.next().unwrap().unwrap_err().hint().map(String::from)
+    };
+
+    assert_eq!(
+        Some("Try 'prog -h' for more information."),
+        hint(&["/usr/bin/prog.exe", "-x"]).as_deref()
+    );
+    assert_eq!(None, hint(&["", "-x"]));
+}
+
+#[test]
+fn input_limits() {
+    let args: Vec<String> = vec!["x", "-v", "-vv", "-a", "1", "-a2", "-b3", "-va4", "-b5"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut opts = Parser::new(&args, "a:b:v");
+    opts.set_max_values(2);
+    opts.set_max_occurrences('b', 1);
+    let kinds: Vec<_> = opts
+        .map(|r| r.map_err(|e| (e.kind(), e.culprit())))
+        .collect();
+    assert_eq!(
+        vec![
+            Ok(Opt('v', None)),
+            Ok(Opt('v', None)),
+            Ok(Opt('v', None)),
+            Ok(Opt('a', Some("1".to_string()))),
+            Ok(Opt('a', Some("2".to_string()))),
+            Ok(Opt('b', Some("3".to_string()))),
+            Ok(Opt('v', None)),
+            Err((ErrorKind::LimitExceeded, 'a')),
+            Err((ErrorKind::TooManyOccurrences, 'b')),
+        ],
+        kinds
+    );
+
+    let args: Vec<String> = vec!["x", "-v", "-vv", "-a", "1", "-a2"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    // over a limit, nothing is parsed, even when errors are skipped
+    let mut opts = Parser::new(&args, "a:v");
+    opts.set_max_arg_len(2);
+    assert!(opts.ok_only().next().is_none());
+    let mut opts = Parser::new(&args, "a:v");
+    opts.set_max_arg_len(3);
+    opts.set_max_args(6);
+    assert_eq!(5, opts.ok_only().count());
+}
+
+#[test]
+fn invalid_value_source() {
+    use std::{error::Error as _, num::ParseIntError};
+
+    let source = "-1".parse::<u8>().unwrap_err();
+    let error = Error::invalid_value('n', "-1", source.clone());
+    let inner = error
+        .source()
+        .and_then(|e| e.downcast_ref::<ParseIntError>());
+    assert_eq!(Some(&source), inner);
+
+    // sources take no part in equality
+    assert_eq!(error, Error::invalid_value('n', "-1", "anything"));
+    assert_ne!(error, Error::invalid_value('n', "-2", "anything"));
+    assert!(Error::new(ErrorKind::UnknownOption, 'n').source().is_none());
+}
+
+#[test]
+fn duration_overflow() {
+    use crate::parsers::Duration;
+
+    for &s in &[
+        "18446744073709551616",
+        "18446744073709551615.5",
+        "1e400",
+        "213503982334602d",
+    ] {
+        assert!(s.parse::<Duration>().is_err(), "{}", s);
+    }
+    assert!("1000000000000s".parse::<Duration>().is_ok());
+}
+
+#[test]
+fn key_values_malformed() {
+    use crate::parsers;
+
+    let opts = vec![
+        Opt('D', Some("A=1".to_string())),
+        Opt('D', Some("B".to_string())),
+    ];
+    let error = parsers::key_values(&opts, 'D').unwrap_err();
+    assert_eq!(ErrorKind::InvalidValue, error.kind());
+    assert_eq!(Some("B"), error.value());
+    assert_eq!(
+        "invalid value \"B\" for option -- 'D': expected NAME=VALUE",
+        error.to_string()
+    );
+}
+
+#[test]
+fn push_parser() {
+    let mut opts = PushParser::new("ab:");
+    let grouped = Layout {
+        grouped: true,
+        attached: false,
+    };
+
+    assert_eq!(
+        vec![
+            Event::Opt(Opt('a', None), Layout::default()),
+            Event::Error(Error::new(ErrorKind::UnknownOption, 'x'), grouped),
+            Event::Opt(
+                Opt('b', Some("c".to_string())),
+                Layout {
+                    grouped: true,
+                    attached: true,
+                }
+            ),
+        ],
+        opts.push_arg("-axbc")
+    );
+    assert!(opts.push_arg("-b").is_empty());
+    assert_eq!(
+        vec![Event::Opt(
+            Opt('b', Some("--".to_string())),
+            Layout::default()
+        )],
+        opts.push_arg("--")
+    );
+    assert_eq!(vec![Event::Separator], opts.push_arg("--"));
+    assert!(opts.is_done());
+    assert_eq!(vec![Event::Operand("--".to_string())], opts.push_arg("--"));
+
+    let mut opts = PushParser::new("ab:");
+    assert_eq!(1, opts.push_arg("-ab").len());
+    assert_eq!(
+        vec![Event::Error(
+            Error::new(ErrorKind::MissingArgument, 'b'),
+            grouped
+        )],
+        opts.finish()
+    );
+}
+
+macro_rules! reconstruct_test {
+    ($name:ident, [$($arg:expr),*], $optstr:expr) => (
+        #[test]
+        fn $name() {
+            let args: Vec<String> = vec![$($arg.to_string()),*];
+            let mut opts = PushParser::new($optstr);
+            let mut events = Vec::new();
+            for arg in &args {
+                events.extend(opts.push_arg(arg));
+            }
+            events.extend(opts.finish());
+
+            assert_eq!(args, reconstruct(&events));
+        }
+    )
+}
+
+#[rustfmt::skip] reconstruct_test!(reconstruct_empty, [], "a");
+#[rustfmt::skip] reconstruct_test!(reconstruct_grouped, ["-aab", "", "-ba", "-b"], "ab:");
+#[rustfmt::skip] reconstruct_test!(reconstruct_errors, ["-xa", "-ax", "-y"], "a");
+#[rustfmt::skip] reconstruct_test!(reconstruct_operands, ["-a", "-", "-a", "--"], "a");
+#[rustfmt::skip] reconstruct_test!(reconstruct_separator, ["-a", "--", "--", "-a"], "a");
+
+#[test]
+fn extract_untouched() -> Result<(), String> {
+    let args: Vec<String> = vec!["x", "-xy", "-", "-a"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let (opts, rest) = extract(&args, "a").map_err(|e| e.to_string())?;
+
+    assert!(opts.is_empty());
+    assert_eq!(args, rest);
+    Ok(())
+}
+
+#[test]
+fn extract_missing_argument() {
+    let args: Vec<String> = vec!["x", "-ab"].into_iter().map(String::from).collect();
+
+    assert_eq!(
+        "option requires an argument -- 'b'",
+        extract(&args, "ab:").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn split_args_bounds() {
+    let args: Vec<String> = vec![];
+    assert_eq!(0, split_args(&args));
+    assert!(args[split_args(&args)..].is_empty());
+
+    let args: Vec<String> = vec!["x".to_string()];
+    assert_eq!(1, split_args(&args));
+
+    let args: Vec<String> = vec!["x", "-a", "-"].into_iter().map(String::from).collect();
+    assert_eq!(2, split_args(&args));
+
+    let args: Vec<String> = vec!["x", "-a", "--"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(3, split_args(&args));
+}
+
+#[cfg(unix)]
+#[test]
+fn new_checked_os() {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    let mut args: Vec<OsString> = vec!["x".into(), "-a".into(), "foo".into()];
+    let mut opts = Parser::new_checked_os(&args, "a").unwrap();
+    assert_eq!(Some(Ok(Opt('a', None))), opts.next());
+
+    args.push(OsString::from_vec(b"b\xffr".to_vec()));
+    let error = Parser::new_checked_os(&args, "a").unwrap_err();
+    assert_eq!(ErrorKind::InvalidUnicode, error.kind());
+    assert_eq!(Some(3), error.index());
+    assert_eq!("argument 3 is not valid Unicode", error.to_string());
+}
+
+#[cfg(unix)]
+#[test]
+fn split_nul_buffers() {
+    use crate::split_nul;
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    assert!(split_nul(b"").is_empty());
+    assert_eq!(vec![OsString::new()], split_nul(b"\0"));
+    assert_eq!(vec!["a", "b"], split_nul(b"a\0b"));
+    assert_eq!(
+        vec![OsString::from("a"), OsString::from_vec(b"\xff".to_vec())],
+        split_nul(b"a\0\xff\0")
+    );
+}
+
+#[test]
+fn lexer_edge_cases() {
+    let args: Vec<String> = vec!["-", "--=x", "--a=b=c", "-a", "--", "--"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let tokens: Vec<Token> = Lexer::new(&args).collect();
+
+    assert_eq!(
+        vec![
+            Token::Operand("-"),
+            Token::LongOption("", Some("x")),
+            Token::LongOption("a", Some("b=c")),
+            Token::ShortCluster("a"),
+            Token::Separator,
+            Token::Operand("--"),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn lexer_assignments() {
+    let args: Vec<String> = vec!["A_1=", "_=x=y", "1A=b", "A-B=c", "=d", "-e=f", "plain"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let mut lexer = Lexer::new(&args);
+    lexer.set_assignments(true);
+    let tokens: Vec<Token> = lexer.collect();
+
+    assert_eq!(
+        vec![
+            Token::Assignment("A_1", ""),
+            Token::Assignment("_", "x=y"),
+            Token::Operand("1A=b"),
+            Token::Operand("A-B=c"),
+            Token::Operand("=d"),
+            Token::ShortCluster("e=f"),
+            Token::Operand("plain"),
+        ],
+        tokens
+    );
+}
+
+macro_rules! split_test {
+    ($name:ident, $expect:expr, $string:expr) => {
+        #[test]
+        fn $name() -> Result<(), String> {
+            let expect: Vec<String> = $expect.iter().map(|s: &&str| s.to_string()).collect();
+
+            match shell::split($string) {
+                Err(error) => Err(format!("split() returned {:?}", error)),
+                Ok(actual) => {
+                    if actual != expect {
+                        Err(format!("expected {:?}; got {:?}", expect, actual))
+                    } else {
+                        Ok(())
+                    }
+                },
+            }
+        }
+    };
+}
+
+#[rustfmt::skip] split_test!(split_blank, [] as [&str; 0], " \t\n ");
+#[rustfmt::skip] split_test!(split_plain, ["-a", "foo", "bar"], "  -a foo\tbar\n");
+#[rustfmt::skip] split_test!(split_single_quotes, ["b c", "\\$x"], "'b c' '\\$x'");
+#[rustfmt::skip] split_test!(split_double_quotes, ["\"$x\" \\n"], "\"\\\"\\$x\\\" \\n\"");
+#[rustfmt::skip] split_test!(split_backslash, ["a b", "c"], "a\\ b c\\\n");
+#[rustfmt::skip] split_test!(split_empty_quotes, ["", "ab"], "'' a\"\"b");
+#[rustfmt::skip] split_test!(split_trailing_backslash, ["a\\"], "a\\");
+
+#[test]
+fn split_unmatched_quote() {
+    assert_eq!(
+        "unmatched quote -- '\\''",
+        shell::split("a 'b").unwrap_err().to_string()
+    );
+    assert_eq!(
+        "unmatched quote -- '\"'",
+        shell::split("a \"b\\\"").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn quote_shells() {
+    use crate::shell::{quote, Shell};
+
+    let string = "a b'c\\d";
+    assert_eq!(r"'a b'\''c\d'", quote(string, Shell::Bourne));
+    assert_eq!(r"'a b'\''c\d'", quote(string, Shell::AnsiC));
+    assert_eq!(r"'a'\ 'b'\''c\d'", quote(string, Shell::C));
+    assert_eq!(r"'a b\'c\\d'", quote(string, Shell::Fish));
+    assert_eq!(r"'a b''c\d'", quote(string, Shell::Rc));
+    assert_eq!(r"$'\x1b[0m\'\n'", quote("\x1b[0m'\n", Shell::AnsiC));
+}
+
+#[test]
+fn unquote_round_trip() {
+    use crate::shell::{quote, unquote, Shell};
+
+    let shells = [
+        Shell::AnsiC,
+        Shell::Bourne,
+        Shell::C,
+        Shell::Fish,
+        Shell::Rc,
+    ];
+    for &string in &["", "plain", "a b", "it's", r"\'\\", "\"$x\"", "tab\there"] {
+        for &shell in &shells {
+            assert_eq!(
+                Ok(string.to_string()),
+                unquote(&quote(string, shell), shell)
+            );
+        }
+    }
+    for &string in &["\x01\x7f", "a\nb\u{1b}[0m", "\x1bc", "a\u{9b}b\u{85}é"] {
+        let quoted = quote(string, Shell::AnsiC);
+        assert_eq!(Ok(string.to_string()), unquote(&quoted, Shell::AnsiC));
+    }
+
+    assert_eq!(
+        Ok("A\u{8}?".to_string()),
+        unquote(r"$'\x41\010\?'", Shell::AnsiC)
+    );
+    // C1 controls are escaped too, as bytes, which bash reproduces in any locale
+    assert_eq!(r"$'a\xc2\x9bb'", quote("a\u{9b}b", Shell::AnsiC));
+    assert_eq!(
+        Ok("\u{e9}\u{ff}".to_string()),
+        unquote(r"$'\xc3\xa9\xff'", Shell::AnsiC)
+    );
+    assert_eq!(
+        Ok("$a\\b".to_string()),
+        unquote(r#""\$a\b""#, Shell::Bourne)
+    );
+    assert_eq!(Ok("\\$a".to_string()), unquote(r#""\$a""#, Shell::C));
+    assert_eq!(
+        "unmatched quote -- '\\''",
+        unquote("'it''s", Shell::Bourne).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn cli_run() {
+    let run = |args: &[&str]| {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        cli::run(&args, &cli::Settings::default())
+    };
+
+    let output = run(&["/bin/getopt", "-s", "fish", "ab:", "-a", "-b", "x", "y"]);
+    assert_eq!(
+        (0, "-a -b 'x' -- 'y'\n", ""),
+        (output.status, &*output.stdout, &*output.stderr)
+    );
+
+    let output = run(&["/bin/getopt", "-n", "prog", "a", "-b"]);
+    assert_eq!(
+        (1, "prog: unknown option -- 'b'\n"),
+        (output.status, &*output.stderr)
+    );
+    // colour is only asked for by the caller, never picked up from the environment
+    let settings = cli::Settings {
+        color: true,
+        ..cli::Settings::default()
+    };
+    let args: Vec<String> = vec!["/bin/getopt", "-n", "prog", "a", "-b"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let output = cli::run(&args, &settings);
+    assert_eq!(1, output.status);
+    assert!(output.stderr.starts_with("prog: unknown option -- 'b'\n"));
+    assert!(output.stderr.contains('\x1b'));
+
+    let output = run(&["/bin/getopt", "-s", "nope", "a"]);
+    assert_eq!(2, output.status);
+    assert_eq!("getopt: unknown shell type: nope\n", output.stderr);
+
+    // an argument that cannot be quoted safely is bad input, not misuse
+    let output = run(&["/bin/getopt", "a", "-a", "x\ny"]);
+    assert_eq!(1, output.status);
+    assert!(output.stdout.is_empty());
+    let output = run(&["/bin/getopt", "b:", "-b", "a\u{9b}b"]);
+    assert_eq!(1, output.status);
+    assert!(output
+        .stderr
+        .ends_with("-- '\\u{9b}'; use -a for ANSI-C quoting\n"));
+    let output = run(&["/bin/getopt", "-a", "b:", "-b", "a\u{9b}b", "\0"]);
+    assert_eq!((1, ""), (output.status, &*output.stdout));
+    let output = run(&["/bin/getopt", "-a", "b:", "-b", "a\u{9b}b"]);
+    assert_eq!(
+        (0, "-b $'a\\xc2\\x9bb' --\n"),
+        (output.status, &*output.stdout)
+    );
+
+    let output = run(&[
+        "/bin/getopt",
+        "-f",
+        "bash",
+        "ab:",
+        "-aab",
+        "x",
+        "-b",
+        "y",
+        "z",
+    ]);
+    assert_eq!(
+        (0, "declare -gA opts=(['a']=2 ['b']='y')\nset -- 'z'\n"),
+        (output.status, &*output.stdout)
+    );
+
+    let output = run(&[
+        "/bin/getopt",
+        "-f",
+        "zsh",
+        "ab:",
+        "-aab",
+        "x",
+        "-b",
+        "y",
+        "z",
+    ]);
+    assert_eq!(
+        (0, "typeset -gA opts\nopts=('-a' '' '-b' 'y')\nset -- 'z'\n"),
+        (output.status, &*output.stdout)
+    );
+
+    // our own options end at "--" when the optstring is not an argument
+    let output = run(&["/bin/getopt", "-o", "ab:", "--", "-a", "-b", "c", "d"]);
+    assert_eq!((0, "-a -b 'c' -- 'd'\n"), (output.status, &*output.stdout));
+
+    // a default optstring comes from the settings, and -o still overrides it
+    let settings = cli::Settings {
+        optstring: Some("ab:".to_string()),
+        ..cli::Settings::default()
+    };
+    let args: Vec<String> = vec!["/bin/getopt", "--", "-a", "-b", "c", "d"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let output = cli::run(&args, &settings);
+    assert_eq!((0, "-a -b 'c' -- 'd'\n"), (output.status, &*output.stdout));
+    let args: Vec<String> = vec!["/bin/getopt", "-o", "a", "--", "-b"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let output = cli::run(&args, &settings);
+    assert_eq!((1, ""), (output.status, &*output.stdout));
+    // without "--", the optstring is an argument as usual, and our own options stay ours
+    let args: Vec<String> = vec!["/bin/getopt", "x:", "-x", "c"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let output = cli::run(&args, &settings);
+    assert_eq!((0, "-x 'c' --\n"), (output.status, &*output.stdout));
+    let args: Vec<String> = vec!["/bin/getopt", "-c"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let output = cli::run(&args, &settings);
+    assert_eq!(
+        (2, "getopt: missing optstring argument\n"),
+        (output.status, &*output.stderr)
+    );
+    let settings = cli::Settings {
+        optstring: Some("c".to_string()),
+        ..cli::Settings::default()
+    };
+    let args: Vec<String> = vec!["/bin/getopt", "--", "-c"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let output = cli::run(&args, &settings);
+    assert_eq!((0, "-c --\n"), (output.status, &*output.stdout));
+
+    // checking neither quotes nor prints anything
+    let output = run(&["/bin/getopt", "-c", "a", "-a", "x\ny"]);
+    assert_eq!(
+        (0, "", ""),
+        (output.status, &*output.stdout, &*output.stderr)
+    );
+    let output = run(&["/bin/getopt", "-c", "a", "-b"]);
+    assert_eq!((1, ""), (output.status, &*output.stdout));
+}
+
+macro_rules! split_windows_test {
+    ($name:ident, $expect:expr, $string:expr) => {
+        #[test]
+        fn $name() {
+            let expect: Vec<String> = $expect.iter().map(|s: &&str| s.to_string()).collect();
+
+            assert_eq!(expect, shell::split_windows($string));
+        }
+    };
+}
+
+#[rustfmt::skip] split_windows_test!(split_windows_blank, [] as [&str; 0], "");
+#[rustfmt::skip] split_windows_test!(split_windows_program, [r"a\b\", "c"], r#""a\b\" c"#);
+#[rustfmt::skip] split_windows_test!(split_windows_quotes, ["x", "a b", "", "c\"d"], "x \"a b\" \"\" \"c\"\"d\"");
+#[rustfmt::skip] split_windows_test!(split_windows_backslashes, ["x", r"a\\b", r"c\", r#"d\"e"#], r#"x a\\b "c\\" d\\\"e"#);
+#[rustfmt::skip] split_windows_test!(split_windows_unterminated, ["x", "a b "], "x \"a b ");
+
+#[cfg(feature = "wildcards")]
+#[test]
+fn wildcards() {
+    use std::{env, fs, process};
+
+    use crate::expand_wildcards;
+
+    let dir = env::temp_dir().join(format!("getopt-wildcards-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in &["a.txt", "B.TXT", "c.rs", "ab.txt"] {
+        fs::File::create(dir.join(name)).unwrap();
+    }
+    let dir = format!("{}/", dir.display());
+
+    let operands: Vec<String> = vec!["*.txt", "?.rs", "*.none", "plain", "*/x"]
+        .into_iter()
+        .map(|p| {
+            if p.contains('.') {
+                format!("{}{}", dir, p)
+            } else {
+                p.to_string()
+            }
+        })
+        .collect();
+    let expected: Vec<String> = vec!["B.TXT", "a.txt", "ab.txt", "c.rs"]
+        .into_iter()
+        .map(|p| format!("{}{}", dir, p))
+        .chain(vec![
+            format!("{}*.none", dir),
+            "plain".to_string(),
+            "*/x".to_string(),
+        ])
+        .collect();
+
+    assert_eq!(expected, expand_wildcards(operands));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,54 @@
+use std::{error::Error, result, str::FromStr};
+
+/// Conversion of an option-argument into a value of some type.
+///
+/// This is implemented for every type that implements
+/// [`FromStr`](https://doc.rust-lang.org/std/str/trait.FromStr.html) with a proper error type,
+/// which covers numbers, `String`, `PathBuf` and so on.
+/// Types that do not implement `FromStr` can implement this trait directly, to be usable with
+/// [`Opt::parse_value`](struct.Opt.html#method.parse_value).
+///
+/// # Example
+///
+/// ```
+/// use getopt::{FromOptValue, Opt};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum When {
+///     Always,
+///     Never,
+/// }
+///
+/// impl FromOptValue for When {
+///     fn from_opt_value(value: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+///         match value {
+///             "always" => Ok(When::Always),
+///             "never" => Ok(When::Never),
+///             _ => Err("expected 'always' or 'never'".into()),
+///         }
+///     }
+/// }
+///
+/// let opt = Opt('c', Some("never".to_string()));
+/// assert_eq!(Some(When::Never), opt.parse_value().unwrap());
+///
+/// let opt = Opt('c', Some("sometimes".to_string()));
+/// assert_eq!(
+///     "invalid value \"sometimes\" for option -- 'c': expected 'always' or 'never'",
+///     opt.parse_value::<When>().unwrap_err().to_string()
+/// );
+/// ```
+pub trait FromOptValue: Sized {
+    /// Convert `value`, or explain why it cannot be converted.
+    fn from_opt_value(value: &str) -> result::Result<Self, Box<dyn Error + Send + Sync>>;
+}
+
+impl<T> FromOptValue for T
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    fn from_opt_value(value: &str) -> result::Result<Self, Box<dyn Error + Send + Sync>> {
+        value.parse().map_err(Into::into)
+    }
+}
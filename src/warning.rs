@@ -0,0 +1,87 @@
+use std::fmt;
+
+/// What kinds of warnings [`Parser`](struct.Parser.html) can record.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// An option marked as deprecated was encountered.
+    Deprecated,
+    /// An argument beginning with `--` was parsed as a group of short options, e.g. `--foo` as
+    /// `-`, `f`, `o`, `o`; the culprit is always `'-'`.
+    LongOptionLike,
+    /// An option that takes an argument was given again, so that its earlier value will most
+    /// likely be overridden.
+    Duplicate,
+}
+
+/// A non-fatal diagnostic recorded by [`Parser`](struct.Parser.html).
+///
+/// Unlike an [`Error`](struct.Error.html), a warning does not interrupt parsing; warnings are
+/// collected and may be retrieved with [`Parser::warnings`](struct.Parser.html#method.warnings).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Warning {
+    culprit: char,
+    kind: WarningKind,
+    replacement: Option<char>,
+}
+
+impl Warning {
+    /// Creates a new warning using a known kind and the character that caused the issue.
+    pub fn new(kind: WarningKind, culprit: char) -> Self {
+        Self {
+            culprit,
+            kind,
+            replacement: None,
+        }
+    }
+
+    /// Creates a new [`Deprecated`](enum.WarningKind.html#variant.Deprecated) warning, naming the
+    /// option that should be used instead, if any.
+    pub fn deprecated(culprit: char, replacement: Option<char>) -> Self {
+        Self {
+            culprit,
+            kind: WarningKind::Deprecated,
+            replacement,
+        }
+    }
+
+    /// Returns the [`WarningKind`](enum.WarningKind.html) for this warning.
+    pub fn kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    /// Returns the option character that caused this warning.
+    pub fn culprit(&self) -> char {
+        self.culprit
+    }
+
+    /// Returns the option that should be used instead of the culprit, if one was given.
+    pub fn replacement(&self) -> Option<char> {
+        self.replacement
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            WarningKind::Deprecated => match self.replacement {
+                None => write!(f, "deprecated option -- {:?}", self.culprit),
+                Some(r) => write!(
+                    f,
+                    "deprecated option -- {:?} (use {:?} instead)",
+                    self.culprit, r
+                ),
+            },
+            WarningKind::LongOptionLike => {
+                write!(
+                    f,
+                    "long option parsed as short options -- {:?}",
+                    self.culprit
+                )
+            },
+            WarningKind::Duplicate => {
+                write!(f, "option given more than once -- {:?}", self.culprit)
+            },
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::fs;
+
+/// Expand `*` and `?` wildcards in `operands`, as a Unix shell would before running a program.
+///
+/// Only available with the `wildcards` feature.
+///
+/// Windows does not expand wildcards on a program's behalf, so tools ported from Unix can use
+/// this on their operands, once option parsing is done, so that option-arguments are never
+/// expanded.
+/// Expansion is performed on every platform; on Unix, where the shell has already done the
+/// work, it is best skipped with `cfg!(windows)`.
+///
+/// Wildcards are only recognised in the last component of a path, and match file names without
+/// regard to ASCII case, as on Windows.
+/// `*` matches any sequence of characters and `?` any single character.
+/// An operand matching nothing is kept as it is; otherwise, it is replaced by its matches, in
+/// sorted order.
+///
+/// # Example
+///
+/// ```no_run
+/// use getopt::{expand_wildcards, Parser};
+///
+/// let mut opts = Parser::from_vec(getopt::cli::args(), "o:");
+/// for opt in opts.by_ref() {
+///     // ...
+/// #   let _ = opt;
+/// }
+///
+/// let mut operands = opts.into_operands();
+/// if cfg!(windows) {
+///     operands = expand_wildcards(operands);
+/// }
+/// ```
+pub fn expand_wildcards(operands: Vec<String>) -> Vec<String> {
+    let mut expanded = Vec::new();
+
+    for operand in operands {
+        match expand(&operand) {
+            Some(ref matches) if !matches.is_empty() => expanded.extend(matches.iter().cloned()),
+            _ => expanded.push(operand),
+        }
+    }
+
+    expanded
+}
+
+fn is_wildcard(c: char) -> bool {
+    c == '*' || c == '?'
+}
+
+// Return the paths matching `pattern`, or `None` if it is not a pattern that can be expanded.
+fn expand(pattern: &str) -> Option<Vec<String>> {
+    let (dir, file_pattern) = match pattern.rfind(|c| c == '/' || c == '\\') {
+        None => ("", pattern),
+        Some(i) => (&pattern[..=i], &pattern[i + 1..]),
+    };
+
+    if !file_pattern.contains(is_wildcard) || dir.contains(is_wildcard) {
+        return None;
+    }
+
+    let file_pattern: Vec<char> = file_pattern.chars().collect();
+    let entries = fs::read_dir(if dir.is_empty() { "." } else { dir }).ok()?;
+    let mut matches: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| {
+            let name: Vec<char> = name.chars().collect();
+            wildcard_match(&file_pattern, &name)
+        })
+        .map(|name| format!("{}{}", dir, name))
+        .collect();
+    matches.sort();
+
+    Some(matches)
+}
+
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // where to resume after the most recent `*`, should the rest fail to match
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, n));
+        } else if p < pattern.len()
+            && (pattern[p] == '?' || pattern[p].eq_ignore_ascii_case(&name[n]))
+        {
+            p += 1;
+            n += 1;
+        } else if let Some((bp, bn)) = backtrack {
+            p = bp;
+            n = bn + 1;
+            backtrack = Some((bp, bn + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}